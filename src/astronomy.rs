@@ -1,8 +1,12 @@
 use crate::dmath;
-use chrono::{Date, Datelike, Utc};
+use crate::ephemeris::{Ephemeris, Usno};
+use crate::Error;
+use chrono::{Date, DateTime, Datelike, Utc};
 
 /// Latitude, Longitude, Altitude (default to 0, in meters)
 ///
+/// Prefer [`Coordinates::new`](Coordinates::new), which validates the values.
+///
 /// # Example
 /// ~~~~
 /// use prayers::*;
@@ -11,8 +15,136 @@ use chrono::{Date, Datelike, Utc};
 /// Coordinates(46.0, 69.0, 25.0);
 /// ~~~~
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coordinates(pub f64, pub f64, pub f64);
 
+impl Coordinates {
+	/// Initialize Coordinates at sea level from a latitude (-90 to 90°) and a longitude,
+	/// normalised to -180 to 180°
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let coords = Coordinates::new(46.0, 249.0)?.with_elevation(25.0)?;
+	/// assert_eq!(coords, Coordinates(46.0, -111.0, 25.0));
+	///
+	/// assert_eq!(Coordinates::new(100.0, 10.0), Err(Error::InvalidLatitude(100.0)));
+	/// # Ok::<(), Error>(())
+	/// ~~~~
+	pub fn new(latitude: f64, longitude: f64) -> Result<Coordinates, Error> {
+		if !(-90.0..=90.0).contains(&latitude) {
+			return Err(Error::InvalidLatitude(latitude));
+		}
+		if !longitude.is_finite() {
+			return Err(Error::InvalidLongitude(longitude));
+		}
+
+		let longitude = dmath::fix_angle(longitude + 180.0) - 180.0;
+		Ok(Coordinates(latitude, longitude, 0.0))
+	}
+
+	/// Set the elevation (in meters)
+	pub fn with_elevation(self, elevation: f64) -> Result<Coordinates, Error> {
+		if !elevation.is_finite() {
+			return Err(Error::InvalidElevation(elevation));
+		}
+
+		Ok(Coordinates(self.0, self.1, elevation))
+	}
+
+	/// Latitude, in degrees
+	pub fn latitude(&self) -> f64 {
+		self.0
+	}
+
+	/// Longitude, in degrees
+	pub fn longitude(&self) -> f64 {
+		self.1
+	}
+
+	/// Elevation, in meters
+	pub fn elevation(&self) -> f64 {
+		self.2
+	}
+}
+
+/// Coordinates with the weather, which bends the light of the sun near the horizon
+///
+/// Without temperature and pressure, the standard atmosphere (10 °C and 1010 hPa) is assumed.
+/// [`Coordinates`](Coordinates) convert into an observer in the standard atmosphere.
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let a_hot_day = Observer::new(a_house).with_temperature(40.0)?.with_pressure(1000.0)?;
+///
+/// assert_eq!(Observer::new(a_house).with_pressure(-1.0), Err(Error::InvalidPressure(-1.0)));
+/// # Ok::<(), Error>(())
+/// ~~~~
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Observer {
+	/// Position
+	pub coords: Coordinates,
+	/// Air temperature, in degrees Celsius
+	pub temperature: Option<f64>,
+	/// Air pressure, in hectopascals (millibars)
+	pub pressure: Option<f64>,
+}
+
+impl Observer {
+	/// Initialize an Observer in the standard atmosphere
+	pub fn new(coords: Coordinates) -> Observer {
+		Observer {
+			coords,
+			temperature: None,
+			pressure: None,
+		}
+	}
+
+	/// Set the air temperature (in degrees Celsius)
+	pub fn with_temperature(self, temperature: f64) -> Result<Observer, Error> {
+		if !temperature.is_finite() || temperature <= -273.15 {
+			return Err(Error::InvalidTemperature(temperature));
+		}
+
+		Ok(Observer {
+			temperature: Some(temperature),
+			..self
+		})
+	}
+
+	/// Set the air pressure (in hectopascals)
+	pub fn with_pressure(self, pressure: f64) -> Result<Observer, Error> {
+		if !pressure.is_finite() || pressure < 0.0 {
+			return Err(Error::InvalidPressure(pressure));
+		}
+
+		Ok(Observer {
+			pressure: Some(pressure),
+			..self
+		})
+	}
+
+	/// The angle of the sun below the horizon at sunrise and sunset, `dip` being the angle
+	/// of the horizon below the observer
+	pub(crate) fn rise_set_angle(&self, dip: f64) -> f64 {
+		// the refraction at the horizon scales with the density of the air (Bennett, Meeus)
+		let density =
+			self.pressure.unwrap_or(1010.0) / 1010.0 * 283.0 / (273.0 + self.temperature.unwrap_or(10.0));
+		0.833 + dip + REFRACTION * (density - 1.0)
+	}
+}
+
+impl From<Coordinates> for Observer {
+	fn from(coords: Coordinates) -> Observer {
+		Observer::new(coords)
+	}
+}
+
 pub fn get_julian_day(date: &Date<Utc>) -> f64 {
 	let mut year = date.year() as f64;
 	let mut month = date.month() as f64;
@@ -26,13 +158,25 @@ pub fn get_julian_day(date: &Date<Utc>) -> f64 {
 	(365.2425 * year + 30.6001 * month).floor() + day + 1721027.5
 }
 
-pub fn mid_day(julian_date: f64, time: f64) -> f64 {
-	let eqt = sun_position(julian_date + time).1;
+/// The julian date at the start of a UTC day
+pub fn julian_date(date: &Date<Utc>) -> f64 {
+	date.num_days_from_ce() as f64 + 1721424.5
+}
+
+pub fn mid_day<E: Ephemeris>(ephemeris: &E, julian_date: f64, time: f64) -> f64 {
+	let eqt = ephemeris.sun_position(julian_date + time).1;
 	dmath::fix_hour(12.0 - eqt)
 }
 
-pub fn sun_angle_time(julian_date: f64, latitude: f64, angle: f64, time: f64, ccw: bool) -> f64 {
-	let (decl, eqt) = sun_position(julian_date + time);
+pub fn sun_angle_time<E: Ephemeris>(
+	ephemeris: &E,
+	julian_date: f64,
+	latitude: f64,
+	angle: f64,
+	time: f64,
+	ccw: bool,
+) -> f64 {
+	let (decl, eqt, _) = ephemeris.sun_position(julian_date + time);
 	let t = 1.0 / 15.0
 		* dmath::arccos(
 			&((-dmath::sin(&angle) - dmath::sin(&decl) * dmath::sin(&latitude))
@@ -42,9 +186,9 @@ pub fn sun_angle_time(julian_date: f64, latitude: f64, angle: f64, time: f64, cc
 	dmath::fix_hour(12.0 - eqt) + if ccw { -t } else { t }
 }
 
-/// (decl, eqt)
+/// (decl, eqt, right ascension in hours)
 #[allow(non_snake_case)]
-pub fn sun_position(jd: f64) -> (f64, f64) {
+pub fn sun_equatorial(jd: f64) -> (f64, f64, f64) {
 	let D = jd - 2451545.0;
 
 	let q = dmath::fix_angle(280.46061837 + 0.98564736 * D);
@@ -53,14 +197,135 @@ pub fn sun_position(jd: f64) -> (f64, f64) {
 	let e = 23.439 - 0.00000036 * D;
 
 	let decl = dmath::arcsin(&(dmath::sin(&e) * dmath::sin(&L)));
-	let eqt = q / 15.0
-		- dmath::fix_hour(dmath::arctan2(&(dmath::cos(&e) * dmath::sin(&L)), &dmath::cos(&L)) / 15.0);
+	let ra =
+		dmath::fix_hour(dmath::arctan2(&(dmath::cos(&e) * dmath::sin(&L)), &dmath::cos(&L)) / 15.0);
+	let eqt = q / 15.0 - ra;
+
+	(decl, eqt, ra)
+}
 
-	return (decl, eqt);
+/// The position of the sun in the sky
+///
+/// Angles are in degrees. The altitude is geometric (without refraction).
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct SolarPosition {
+	/// Declination
+	pub declination: f64,
+	/// Equation of time (apparent minus mean solar time), in minutes
+	pub equation_of_time: f64,
+	/// Right ascension, in hours (0 to 24)
+	pub right_ascension: f64,
+	/// Local hour angle (-180 to 180°), negative before and positive after the mid-day
+	pub hour_angle: f64,
+	/// Altitude above the horizon (-90 to 90°)
+	pub altitude: f64,
+	/// Azimuth, clockwise from the north (0 to 360°)
+	pub azimuth: f64,
 }
 
-pub fn rise_set_angle(elevation: f64) -> f64 {
+impl SolarPosition {
+	/// The position `hours` after the start of the UTC day of a julian date
+	pub(crate) fn at<E: Ephemeris>(
+		ephemeris: &E,
+		julian_date: f64,
+		hours: f64,
+		coords: Coordinates,
+	) -> SolarPosition {
+		let (decl, eqt, ra) = ephemeris.sun_position(julian_date + hours / 24.0);
+		let hour_angle =
+			dmath::fix_angle(15.0 * (hours + coords.1 / 15.0 + eqt - 12.0) + 180.0) - 180.0;
+
+		let altitude = dmath::arcsin(
+			&(dmath::sin(&coords.0) * dmath::sin(&decl)
+				+ dmath::cos(&coords.0) * dmath::cos(&decl) * dmath::cos(&hour_angle)),
+		);
+		let azimuth = dmath::fix_angle(
+			dmath::arctan2(
+				&dmath::sin(&hour_angle),
+				&(dmath::cos(&hour_angle) * dmath::sin(&coords.0)
+					- dmath::tan(&decl) * dmath::cos(&coords.0)),
+			) + 180.0,
+		);
+
+		SolarPosition {
+			declination: decl,
+			equation_of_time: eqt * 60.0,
+			right_ascension: ra,
+			hour_angle,
+			altitude,
+			azimuth,
+		}
+	}
+
+	/// The length of the shadow of a vertical object, relative to its height
+	/// (`None` when the sun is below the horizon)
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+	/// let sun = solar_position(a_house, Utc.ymd(2021, 4, 12).and_hms(17, 9, 0));
+	/// assert_eq!(format!("{:.2}", sun.shadow_ratio().unwrap()), "0.58");
+	/// ~~~~
+	pub fn shadow_ratio(&self) -> Option<f64> {
+		if self.altitude > 0.0 {
+			Some(1.0 / dmath::tan(&self.altitude))
+		} else {
+			None
+		}
+	}
+}
+
+/// Get the position of the sun at an instant, seen from coordinates
+///
+/// Uses the [`Usno`](Usno) ephemeris, see [`solar_position_with`](solar_position_with).
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let sun = solar_position(a_house, Utc.ymd(2021, 4, 12).and_hms(17, 9, 0));
+/// assert!(sun.hour_angle.abs() < 1.0);
+/// assert!((sun.azimuth - 180.0).abs() < 1.0);
+/// ~~~~
+pub fn solar_position(coords: Coordinates, time: DateTime<Utc>) -> SolarPosition {
+	solar_position_with(&Usno, coords, time)
+}
+
+/// Get the position of the sun at an instant, seen from coordinates, with an ephemeris
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let sun = solar_position_with(&Meeus::new(), a_house, Utc.ymd(2021, 4, 12).and_hms(17, 9, 0));
+/// assert!(sun.hour_angle.abs() < 1.0);
+/// ~~~~
+pub fn solar_position_with<E: Ephemeris>(
+	ephemeris: &E,
+	coords: Coordinates,
+	time: DateTime<Utc>,
+) -> SolarPosition {
+	let date = time.date();
+	let hours = time
+		.signed_duration_since(date.and_hms(0, 0, 0))
+		.num_milliseconds() as f64
+		/ 3_600_000.0;
+	SolarPosition::at(ephemeris, julian_date(&date), hours, coords)
+}
+
+/// Refraction at the horizon in the standard atmosphere, in degrees (34′)
+const REFRACTION: f64 = 0.567;
+
+/// The angle of a horizon at an elevation below an observer at another (in meters)
+pub fn dip(elevation: f64, horizon: f64) -> f64 {
+	if elevation <= horizon {
+		return 0.0;
+	}
+
 	let earth_radius = 6371008.7714; // in meters
-	let angle = dmath::arccos(&(earth_radius / (earth_radius + elevation)));
-	return 0.833 + angle;
+	dmath::arccos(&((earth_radius + horizon) / (earth_radius + elevation)))
 }
@@ -0,0 +1,219 @@
+use crate::astronomy::{get_julian_day, julian_date, sun_equatorial};
+use crate::dmath;
+use chrono::{Date, Utc};
+
+/// A model of the apparent motion of the sun
+///
+/// The prayer times only need the position of the sun: a [`PrayerManager`](crate::PrayerManager)
+/// uses [`Usno`](Usno) by default, and another ephemeris with
+/// [`PrayerManager::with_ephemeris`](crate::PrayerManager::with_ephemeris).
+pub trait Ephemeris {
+	/// The julian date of the start of a UTC day
+	fn julian_day(&self, date: &Date<Utc>) -> f64;
+
+	/// The apparent position of the sun at a julian date (in universal time):
+	/// declination (in degrees), equation of time and right ascension (in hours)
+	fn sun_position(&self, julian_date: f64) -> (f64, f64, f64);
+}
+
+/// The approximation of the U.S. Naval Observatory used by PrayTimes (the default)
+///
+/// Accurate to about 0.01° until 2050, without nutation nor ΔT. The julian day of a date
+/// is approximated as in PrayTimes, up to a day later than the actual julian date.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub struct Usno;
+
+impl Ephemeris for Usno {
+	fn julian_day(&self, date: &Date<Utc>) -> f64 {
+		get_julian_day(date)
+	}
+
+	fn sun_position(&self, julian_date: f64) -> (f64, f64, f64) {
+		sun_equatorial(julian_date)
+	}
+}
+
+/// The solar coordinates of Jean Meeus' *Astronomical Algorithms* (chapters 22, 25 and 28)
+///
+/// Evaluated in dynamical time, with the nutation in longitude and obliquity and the
+/// aberration, from the actual julian date of each day. ΔT (TT − UT) is estimated with
+/// the polynomials of Espenak and Meeus, unless set with [`with_delta_t`](Meeus::with_delta_t).
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle))
+///     .with_ephemeris(Meeus::new());
+///
+/// let a_date = Utc.ymd(2021, 4, 12);
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let prayers = prayer_manager.get_times(a_date, a_house);
+/// ~~~~
+#[derive(PartialEq, Debug, Copy, Clone, Default)]
+pub struct Meeus {
+	delta_t: Option<f64>,
+}
+
+impl Meeus {
+	/// Initialize the ephemeris, estimating ΔT
+	pub fn new() -> Meeus {
+		Meeus { delta_t: None }
+	}
+
+	/// Set ΔT (TT − UT), in seconds
+	pub fn with_delta_t(mut self, delta_t: f64) -> Meeus {
+		self.delta_t = Some(delta_t);
+		self
+	}
+
+	/// ΔT (TT − UT) at a julian date, in seconds
+	pub fn delta_t(&self, julian_date: f64) -> f64 {
+		self
+			.delta_t
+			.unwrap_or_else(|| estimate_delta_t(2000.0 + (julian_date - 2451545.0) / 365.25))
+	}
+}
+
+impl Ephemeris for Meeus {
+	fn julian_day(&self, date: &Date<Utc>) -> f64 {
+		julian_date(date)
+	}
+
+	#[allow(non_snake_case)]
+	fn sun_position(&self, julian_date: f64) -> (f64, f64, f64) {
+		let jde = julian_date + self.delta_t(julian_date) / 86400.0;
+		let T = (jde - 2451545.0) / 36525.0;
+
+		// geometric mean longitude, mean anomaly and eccentricity of the orbit of the Earth
+		let L0 = dmath::fix_angle(280.46646 + T * (36000.76983 + T * 0.0003032));
+		let M = dmath::fix_angle(357.52911 + T * (35999.05029 - T * 0.0001537));
+		let e = 0.016708634 - T * (0.000042037 + T * 0.0000001267);
+
+		let C = (1.914602 - T * (0.004817 + T * 0.000014)) * dmath::sin(&M)
+			+ (0.019993 - T * 0.000101) * dmath::sin(&(2.0 * M))
+			+ 0.000289 * dmath::sin(&(3.0 * M));
+		let longitude = L0 + C;
+		let anomaly = M + C;
+		let radius = 1.000001018 * (1.0 - e * e) / (1.0 + e * dmath::cos(&anomaly));
+
+		// nutation, in degrees
+		let omega = 125.04452 - T * (1934.136261 - T * (0.0020708 + T / 450000.0));
+		let sun = 280.4665 + 36000.7698 * T;
+		let moon = 218.3165 + 481267.8813 * T;
+		let nutation_longitude = (-17.20 * dmath::sin(&omega)
+			- 1.32 * dmath::sin(&(2.0 * sun))
+			- 0.23 * dmath::sin(&(2.0 * moon))
+			+ 0.21 * dmath::sin(&(2.0 * omega)))
+			/ 3600.0;
+		let nutation_obliquity = (9.20 * dmath::cos(&omega)
+			+ 0.57 * dmath::cos(&(2.0 * sun))
+			+ 0.10 * dmath::cos(&(2.0 * moon))
+			- 0.09 * dmath::cos(&(2.0 * omega)))
+			/ 3600.0;
+
+		let mean_obliquity =
+			23.0 + (26.0 + (21.448 - T * (46.8150 + T * (0.00059 - T * 0.001813))) / 60.0) / 60.0;
+		let obliquity = mean_obliquity + nutation_obliquity;
+		let apparent = longitude + nutation_longitude - 20.4898 / 3600.0 / radius;
+
+		let decl = dmath::arcsin(&(dmath::sin(&obliquity) * dmath::sin(&apparent)));
+		let ra = dmath::fix_angle(dmath::arctan2(
+			&(dmath::cos(&obliquity) * dmath::sin(&apparent)),
+			&dmath::cos(&apparent),
+		));
+		let eqt = L0 - 0.0057183 - ra + nutation_longitude * dmath::cos(&obliquity);
+		let eqt = dmath::fix_angle(eqt + 180.0) - 180.0;
+
+		(decl, eqt / 15.0, ra / 15.0)
+	}
+}
+
+/// ΔT (TT − UT) in seconds for a decimal year, by the polynomials of Espenak and Meeus
+fn estimate_delta_t(year: f64) -> f64 {
+	let long_term = |year: f64| -20.0 + 32.0 * ((year - 1820.0) / 100.0).powi(2);
+	let polynomial = |t: f64, coefficients: &[f64]| {
+		coefficients
+			.iter()
+			.rev()
+			.fold(0.0, |sum, coefficient| sum * t + coefficient)
+	};
+
+	match year {
+		y if y < -500.0 => long_term(y),
+		y if y < 500.0 => polynomial(
+			y / 100.0,
+			&[
+				10583.6,
+				-1014.41,
+				33.78311,
+				-5.952053,
+				-0.1798452,
+				0.022174192,
+				0.0090316521,
+			],
+		),
+		y if y < 1600.0 => polynomial(
+			(y - 1000.0) / 100.0,
+			&[
+				1574.2,
+				-556.01,
+				71.23472,
+				0.319781,
+				-0.8503463,
+				-0.005050998,
+				0.0083572073,
+			],
+		),
+		y if y < 1700.0 => polynomial(y - 1600.0, &[120.0, -0.9808, -0.01532, 1.0 / 7129.0]),
+		y if y < 1800.0 => polynomial(
+			y - 1700.0,
+			&[8.83, 0.1603, -0.0059285, 0.00013336, -1.0 / 1174000.0],
+		),
+		y if y < 1860.0 => polynomial(
+			y - 1800.0,
+			&[
+				13.72,
+				-0.332447,
+				0.0068612,
+				0.0041116,
+				-0.00037436,
+				0.0000121272,
+				-0.0000001699,
+				0.000000000875,
+			],
+		),
+		y if y < 1900.0 => polynomial(
+			y - 1860.0,
+			&[
+				7.62,
+				0.5737,
+				-0.251754,
+				0.01680668,
+				-0.0004473624,
+				1.0 / 233174.0,
+			],
+		),
+		y if y < 1920.0 => polynomial(
+			y - 1900.0,
+			&[-2.79, 1.494119, -0.0598939, 0.0061966, -0.000197],
+		),
+		y if y < 1941.0 => polynomial(y - 1920.0, &[21.20, 0.84493, -0.076100, 0.0020936]),
+		y if y < 1961.0 => polynomial(y - 1950.0, &[29.07, 0.407, -1.0 / 233.0, 1.0 / 2547.0]),
+		y if y < 1986.0 => polynomial(y - 1975.0, &[45.45, 1.067, -1.0 / 260.0, -1.0 / 718.0]),
+		y if y < 2005.0 => polynomial(
+			y - 2000.0,
+			&[
+				63.86,
+				0.3345,
+				-0.060374,
+				0.0017275,
+				0.000651814,
+				0.00002373599,
+			],
+		),
+		y if y < 2050.0 => polynomial(y - 2000.0, &[62.92, 0.32217, 0.005589]),
+		y if y < 2150.0 => long_term(y) - 0.5628 * (2150.0 - y),
+		y => long_term(y),
+	}
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// An error
+#[derive(PartialEq, Debug, Clone)]
+pub enum Error {
+	/// A required parameter is not set
+	MissingParameter(&'static str),
+	/// An angle is not within 0–90° (parameter, value)
+	InvalidAngle(&'static str, f64),
+	/// A minutes offset is negative or not finite (parameter, value)
+	InvalidMinutes(&'static str, f64),
+	/// A latitude is not within -90–90°
+	InvalidLatitude(f64),
+	/// A longitude is not finite
+	InvalidLongitude(f64),
+	/// An elevation is not finite
+	InvalidElevation(f64),
+	/// A temperature is below absolute zero or not finite
+	InvalidTemperature(f64),
+	/// A pressure is negative or not finite
+	InvalidPressure(f64),
+	/// A name is not known (kind, name)
+	UnknownName(&'static str, String),
+	/// A value cannot be parsed (parameter, value)
+	InvalidValue(&'static str, String),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::MissingParameter(parameter) => write!(f, "missing {} parameter", parameter),
+			Error::InvalidAngle(parameter, value) => {
+				write!(
+					f,
+					"invalid {} angle: {}° (expected 0–90°)",
+					parameter, value
+				)
+			}
+			Error::InvalidMinutes(parameter, value) if parameter.ends_with(" tune") => write!(
+				f,
+				"invalid {} minutes: {} (expected a finite number)",
+				parameter, value
+			),
+			Error::InvalidMinutes(parameter, value) => write!(
+				f,
+				"invalid {} minutes: {} (expected a positive number)",
+				parameter, value
+			),
+			Error::InvalidLatitude(value) => {
+				write!(f, "invalid latitude: {}° (expected -90–90°)", value)
+			}
+			Error::InvalidLongitude(value) => write!(f, "invalid longitude: {}°", value),
+			Error::InvalidElevation(value) => write!(f, "invalid elevation: {} m", value),
+			Error::InvalidTemperature(value) => write!(f, "invalid temperature: {} °C", value),
+			Error::InvalidPressure(value) => write!(f, "invalid pressure: {} hPa", value),
+			Error::UnknownName(kind, name) => write!(f, "unknown {}: {}", kind, name),
+			Error::InvalidValue(parameter, value) => write!(f, "invalid {} value: {}", parameter, value),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
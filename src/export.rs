@@ -0,0 +1,349 @@
+use crate::{Ephemeris, Prayer, PrayerTimes, Timetable};
+use chrono::{Date, DateTime, Duration, DurationRound, SecondsFormat, TimeZone, Utc};
+use std::fmt;
+use std::io::{self, Write};
+
+/// A clock format
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Clock {
+	/// 24-hour clock (`17:05`)
+	H24,
+	/// 12-hour clock (`5:05 PM`)
+	H12,
+}
+
+impl Clock {
+	/// Format a time rounded to the nearest minute
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let a_time = Utc.ymd(2021, 4, 12).and_hms(17, 4, 45);
+	/// assert_eq!(Clock::H24.format(&a_time), "17:05");
+	/// assert_eq!(Clock::H12.format(&a_time), "5:05 PM");
+	/// ~~~~
+	pub fn format<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> String
+	where
+		Tz::Offset: fmt::Display,
+	{
+		let format = match self {
+			Clock::H24 => "%H:%M",
+			Clock::H12 => "%-I:%M %p",
+		};
+		round(time, Duration::minutes(1)).format(format).to_string()
+	}
+}
+
+/// Writes timetables as CSV
+///
+/// The header is `date` followed by the [names](Prayer::name) of the columns, then each day
+/// is a row: the date (`YYYY-MM-DD`) and its times rounded to the minute, in the time zone of
+/// the timetable (see [`PrayerManager::timetable`](crate::PrayerManager::timetable)).
+/// Undefined times are empty.
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+///
+/// let a_zone = FixedOffset::west(4 * 3600);
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let april = prayer_manager.timetable(a_zone.ymd(2021, 4, 1), a_zone.ymd(2021, 4, 30), a_house);
+///
+/// let mut csv = Vec::new();
+/// CsvWriter::new()
+///     .with_columns(&[Prayer::Fajr, Prayer::Dhuhr, Prayer::Maghrib])
+///     .with_clock(Clock::H12)
+///     .write(&mut csv, april)?;
+///
+/// let csv = String::from_utf8(csv).unwrap();
+/// assert_eq!(csv.lines().next(), Some("date,fajr,dhuhr,maghrib"));
+/// # Ok::<(), std::io::Error>(())
+/// ~~~~
+#[derive(PartialEq, Debug, Clone)]
+pub struct CsvWriter {
+	columns: Vec<Prayer>,
+	clock: Clock,
+}
+
+impl Default for CsvWriter {
+	fn default() -> CsvWriter {
+		CsvWriter::new()
+	}
+}
+
+impl CsvWriter {
+	/// Initialize a CsvWriter with every prayer as column and a 24-hour clock
+	pub fn new() -> CsvWriter {
+		CsvWriter {
+			columns: Prayer::ALL.to_vec(),
+			clock: Clock::H24,
+		}
+	}
+
+	/// Set the columns, in order
+	pub fn with_columns(mut self, columns: &[Prayer]) -> CsvWriter {
+		self.columns = columns.to_vec();
+		self
+	}
+
+	/// Set the clock format
+	pub fn with_clock(mut self, clock: Clock) -> CsvWriter {
+		self.clock = clock;
+		self
+	}
+
+	/// Write the days of a timetable
+	pub fn write<W, Tz, I>(&self, mut writer: W, days: I) -> io::Result<()>
+	where
+		W: Write,
+		Tz: TimeZone,
+		Tz::Offset: fmt::Display,
+		I: IntoIterator<Item = (Date<Tz>, PrayerTimes<Option<DateTime<Tz>>>)>,
+	{
+		write!(writer, "date")?;
+		for prayer in self.columns.iter() {
+			write!(writer, ",{}", prayer.name())?;
+		}
+		writeln!(writer)?;
+
+		for (date, times) in days {
+			write!(writer, "{}", date.format("%Y-%m-%d"))?;
+			for prayer in self.columns.iter() {
+				write!(writer, ",")?;
+				if let Some(time) = times.get(*prayer) {
+					write!(writer, "{}", self.clock.format(time))?;
+				}
+			}
+			writeln!(writer)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Writes timetables as JSON
+///
+/// The timetable is an array with an object per day: its `date` (`YYYY-MM-DD`) and
+/// the times of the columns, keyed by [name](Prayer::name), as ISO 8601 timestamps rounded
+/// to the second in the time zone of the timetable. Undefined times are `null`.
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+///
+/// let a_zone = FixedOffset::west(4 * 3600);
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let a_day = prayer_manager.timetable(a_zone.ymd(2021, 4, 12), a_zone.ymd(2021, 4, 12), a_house);
+///
+/// let mut json = Vec::new();
+/// JsonWriter::new()
+///     .with_columns(&[Prayer::Fajr])
+///     .write(&mut json, a_day)?;
+///
+/// let json = String::from_utf8(json).unwrap();
+/// assert_eq!(json, "[\n  {\"date\": \"2021-04-12\", \"fajr\": \"2021-04-12T05:01:36-04:00\"}\n]\n");
+/// # Ok::<(), std::io::Error>(())
+/// ~~~~
+#[derive(PartialEq, Debug, Clone)]
+pub struct JsonWriter {
+	columns: Vec<Prayer>,
+}
+
+impl Default for JsonWriter {
+	fn default() -> JsonWriter {
+		JsonWriter::new()
+	}
+}
+
+impl JsonWriter {
+	/// Initialize a JsonWriter with every prayer as column
+	pub fn new() -> JsonWriter {
+		JsonWriter {
+			columns: Prayer::ALL.to_vec(),
+		}
+	}
+
+	/// Set the columns, in order
+	pub fn with_columns(mut self, columns: &[Prayer]) -> JsonWriter {
+		self.columns = columns.to_vec();
+		self
+	}
+
+	/// Write the days of a timetable
+	pub fn write<W, Tz, I>(&self, mut writer: W, days: I) -> io::Result<()>
+	where
+		W: Write,
+		Tz: TimeZone,
+		Tz::Offset: fmt::Display,
+		I: IntoIterator<Item = (Date<Tz>, PrayerTimes<Option<DateTime<Tz>>>)>,
+	{
+		write!(writer, "[")?;
+
+		for (index, (date, times)) in days.into_iter().enumerate() {
+			if index > 0 {
+				write!(writer, ",")?;
+			}
+			write!(writer, "\n  {{\"date\": \"{}\"", date.format("%Y-%m-%d"))?;
+			for prayer in self.columns.iter() {
+				match times.get(*prayer) {
+					Some(time) => write!(
+						writer,
+						", \"{}\": \"{}\"",
+						prayer.name(),
+						round(time, Duration::seconds(1)).to_rfc3339_opts(SecondsFormat::Secs, false)
+					)?,
+					None => write!(writer, ", \"{}\": null", prayer.name())?,
+				}
+			}
+			write!(writer, "}}")?;
+		}
+
+		writeln!(writer, "\n]")
+	}
+}
+
+/// Writes timetables as iCalendar (RFC 5545) events
+///
+/// Each time of the selected prayers (the five daily prayers by default) is a `VEVENT`,
+/// starting at the time rounded to the minute, in UTC. Undefined times are skipped.
+/// The UIDs are made of the date, the prayer and the coordinates, so that importing
+/// a timetable again updates its events instead of duplicating them.
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+///
+/// let a_zone = FixedOffset::west(4 * 3600);
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let april = prayer_manager.timetable(a_zone.ymd(2021, 4, 1), a_zone.ymd(2021, 4, 30), a_house);
+///
+/// let mut ics = Vec::new();
+/// IcalWriter::new()
+///     .with_alarm(10)
+///     .with_duration(15)
+///     .write(&mut ics, april)?;
+///
+/// let ics = String::from_utf8(ics).unwrap();
+/// assert_eq!(ics.matches("BEGIN:VEVENT").count(), 5 * 30);
+/// # Ok::<(), std::io::Error>(())
+/// ~~~~
+#[derive(PartialEq, Debug, Clone)]
+pub struct IcalWriter {
+	prayers: Vec<Prayer>,
+	alarm: Option<u32>,
+	duration: Option<u32>,
+}
+
+impl Default for IcalWriter {
+	fn default() -> IcalWriter {
+		IcalWriter::new()
+	}
+}
+
+impl IcalWriter {
+	/// Initialize an IcalWriter with fajr, dhuhr, asr, maghrib and isha, without alarms
+	/// and with events lasting no time
+	pub fn new() -> IcalWriter {
+		IcalWriter {
+			prayers: vec![
+				Prayer::Fajr,
+				Prayer::Dhuhr,
+				Prayer::Asr,
+				Prayer::Maghrib,
+				Prayer::Isha,
+			],
+			alarm: None,
+			duration: None,
+		}
+	}
+
+	/// Set the prayers to write events for
+	pub fn with_prayers(mut self, prayers: &[Prayer]) -> IcalWriter {
+		self.prayers = prayers.to_vec();
+		self
+	}
+
+	/// Add a reminder the given minutes before each event
+	pub fn with_alarm(mut self, minutes: u32) -> IcalWriter {
+		self.alarm = Some(minutes);
+		self
+	}
+
+	/// Set the duration of the events, in minutes
+	pub fn with_duration(mut self, minutes: u32) -> IcalWriter {
+		self.duration = Some(minutes);
+		self
+	}
+
+	/// Write a calendar with the events of a timetable
+	pub fn write<W: Write, Tz: TimeZone, E: Ephemeris>(
+		&self,
+		mut writer: W,
+		timetable: Timetable<'_, Tz, E>,
+	) -> io::Result<()> {
+		let coords = timetable.coordinates();
+		let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+
+		write!(writer, "BEGIN:VCALENDAR\r\n")?;
+		write!(writer, "VERSION:2.0\r\n")?;
+		write!(writer, "PRODID:-//praye.rs//prayers//EN\r\n")?;
+		write!(writer, "CALSCALE:GREGORIAN\r\n")?;
+
+		for (date, times) in timetable {
+			for prayer in self.prayers.iter() {
+				let time = match times.get(*prayer) {
+					Some(time) => round(time, Duration::minutes(1)).with_timezone(&Utc),
+					None => continue,
+				};
+				let summary = summary(*prayer);
+
+				write!(writer, "BEGIN:VEVENT\r\n")?;
+				write!(
+					writer,
+					"UID:{}-{}-{:.4}_{:.4}@praye.rs\r\n",
+					date.naive_local().format("%Y%m%d"),
+					prayer.name(),
+					coords.0,
+					coords.1
+				)?;
+				write!(writer, "DTSTAMP:{}\r\n", stamp)?;
+				write!(writer, "DTSTART:{}\r\n", time.format("%Y%m%dT%H%M%SZ"))?;
+				if let Some(duration) = self.duration {
+					write!(writer, "DURATION:PT{}M\r\n", duration)?;
+				}
+				write!(writer, "SUMMARY:{}\r\n", summary)?;
+				if let Some(alarm) = self.alarm {
+					write!(writer, "BEGIN:VALARM\r\n")?;
+					write!(writer, "ACTION:DISPLAY\r\n")?;
+					write!(writer, "DESCRIPTION:{}\r\n", summary)?;
+					write!(writer, "TRIGGER:-PT{}M\r\n", alarm)?;
+					write!(writer, "END:VALARM\r\n")?;
+				}
+				write!(writer, "END:VEVENT\r\n")?;
+			}
+		}
+
+		write!(writer, "END:VCALENDAR\r\n")
+	}
+}
+
+/// The capitalized name of a prayer
+fn summary(prayer: Prayer) -> String {
+	let name = prayer.name();
+	name[..1].to_uppercase() + &name[1..]
+}
+
+/// Round a time to the nearest multiple of a duration
+fn round<Tz: TimeZone>(time: &DateTime<Tz>, duration: Duration) -> DateTime<Tz> {
+	time
+		.clone()
+		.duration_round(duration)
+		.unwrap_or_else(|_| time.clone())
+}
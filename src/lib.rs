@@ -1,17 +1,46 @@
+// `chrono::Date` is deprecated upstream but remains the date type of the public API
+#![allow(deprecated)]
+
 mod astronomy;
 mod dmath;
+mod ephemeris;
+mod error;
+mod export;
+mod moonsighting;
+mod names;
 mod prayer;
+mod qibla;
 
-pub use crate::astronomy::Coordinates;
-pub use chrono::{Date, DateTime, Datelike, TimeZone, Timelike, Utc};
+pub use crate::astronomy::{
+	solar_position, solar_position_with, Coordinates, Observer, SolarPosition,
+};
+pub use crate::ephemeris::{Ephemeris, Meeus, Usno};
+pub use crate::error::Error;
+pub use crate::export::{Clock, CsvWriter, IcalWriter, JsonWriter};
+pub use crate::moonsighting::Shafaq;
+pub use crate::qibla::{
+	kaaba_transits, kaaba_transits_with, qibla, qibla_times, qibla_times_with, Qibla, QiblaDirection,
+	QiblaTime, KAABA,
+};
+pub use chrono::{
+	Date, DateTime, Datelike, Duration, FixedOffset, NaiveDate, TimeZone, Timelike, Utc,
+};
+#[cfg(feature = "chrono-tz")]
+pub use chrono_tz;
 pub use prayer::{
-	AsrJuristic, CalculationMethod, CalculationMethods, CalculationType, HightLatMethods,
-	MidnightMethod, PrayerManager, PrayerTimes,
+	AsrJuristic, CalculationMethod, CalculationMethodBuilder, CalculationMethods, CalculationType,
+	ElevationMethod, HightLatMethods, Iterations, MidnightMethod, PolarMethods, Prayer,
+	PrayerManager, PrayerPeriod, PrayerTimes, TimeStatus, Timetable, Tune,
 };
 
 #[cfg(test)]
 mod tests {
-	use super::{CalculationMethods, Coordinates, HightLatMethods, PrayerManager, TimeZone, Utc};
+	use super::{
+		AsrJuristic, CalculationMethod, CalculationMethods, CalculationType, Clock, Coordinates,
+		CsvWriter, Duration, Error, FixedOffset, HightLatMethods, IcalWriter, Iterations, JsonWriter,
+		MidnightMethod, NaiveDate, PolarMethods, Prayer, PrayerManager, Shafaq, TimeStatus, TimeZone,
+		Tune, Utc,
+	};
 
 	#[test]
 	fn compute_prayer_times() {
@@ -32,4 +61,1351 @@ mod tests {
 		assert_eq!(times.isha, 25.1845331305664);
 		assert_eq!(times.midnight, 29.152168579286247);
 	}
+
+	#[test]
+	fn compute_prayer_date_times() {
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+
+		let a_date = Utc.ymd(2021, 4, 12);
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let times = prayer_manager.get_date_times(a_date, a_house);
+
+		assert_eq!(times.fajr, Some(Utc.ymd(2021, 4, 12).and_hms_milli(9, 1, 36, 321)));
+		assert_eq!(times.isha, Some(Utc.ymd(2021, 4, 13).and_hms_milli(1, 11, 4, 319)));
+		assert_eq!(times.midnight, Some(Utc.ymd(2021, 4, 13).and_hms_milli(5, 9, 7, 807)));
+
+		let a_date = Utc.ymd(2021, 6, 21);
+		let a_house = Coordinates(51.5073509, -0.1277583, 0.0);
+		let times = PrayerManager::new(CalculationMethods::MWL, None).get_date_times(a_date, a_house);
+
+		assert_eq!(times.fajr, None);
+		assert!(times.sunrise.is_some());
+	}
+
+	#[test]
+	fn report_prayer_times_status() {
+		let a_date = Utc.ymd(2021, 6, 21);
+		let a_house = Coordinates(51.5073509, -0.1277583, 0.0);
+
+		let times = PrayerManager::new(CalculationMethods::MWL, None).get_times(a_date, a_house);
+		assert!(times.fajr.is_nan());
+		assert_eq!(times.status(Prayer::Imsak), TimeStatus::Undefined);
+		assert_eq!(times.status(Prayer::Fajr), TimeStatus::Undefined);
+		assert_eq!(times.status(Prayer::Sunrise), TimeStatus::Computed);
+		assert_eq!(times.status(Prayer::Isha), TimeStatus::Undefined);
+		assert_eq!(times.status(Prayer::Midnight), TimeStatus::Computed);
+
+		let times = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::AngleBased))
+			.get_times(a_date, a_house);
+		assert!(times.fajr.is_finite());
+		assert_eq!(times.status(Prayer::Imsak), TimeStatus::Adjusted);
+		assert_eq!(times.status(Prayer::Fajr), TimeStatus::Adjusted);
+		assert_eq!(times.status(Prayer::Maghrib), TimeStatus::Computed);
+		assert_eq!(times.status(Prayer::Isha), TimeStatus::Adjusted);
+
+		let a_date = Utc.ymd(2021, 4, 12);
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let times = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle))
+			.get_times(a_date, a_house);
+		assert_eq!(times.status(Prayer::Fajr), TimeStatus::Computed);
+		assert_eq!(times.status(Prayer::Isha), TimeStatus::Computed);
+
+		// polar night: no sunrise, sunset nor shadow of asr
+		let a_date = Utc.ymd(2021, 12, 21);
+		let tromso = Coordinates(69.6492047, 18.9553238, 0.0);
+		let times = PrayerManager::new(CalculationMethods::MWL, None).get_times(a_date, tromso);
+		assert_eq!(times.status(Prayer::Sunrise), TimeStatus::Undefined);
+		assert_eq!(times.status(Prayer::Asr), TimeStatus::Undefined);
+		assert_eq!(times.status(Prayer::Sunset), TimeStatus::Undefined);
+		assert_eq!(times.status(Prayer::Dhuhr), TimeStatus::Computed);
+	}
+
+	#[test]
+	fn compute_projected_high_latitude_times() {
+		let a_date = Utc.ymd(2021, 6, 21);
+		let a_house = Coordinates(51.5073509, -0.1277583, 0.0);
+		let a_reference = Coordinates(48.5, -0.1277583, 0.0);
+		let without = PrayerManager::new(CalculationMethods::MWL, None);
+		let reference = without.get_times(a_date, a_reference);
+
+		let times = PrayerManager::new(
+			CalculationMethods::MWL,
+			Some(HightLatMethods::NearestLatitude(48.5)),
+		)
+		.get_times(a_date, a_house);
+		assert_eq!(times.fajr, reference.fajr);
+		assert_eq!(times.isha, reference.isha);
+		assert_eq!(times.status(Prayer::Fajr), TimeStatus::Adjusted);
+		assert_eq!(times.status(Prayer::Sunrise), TimeStatus::Computed);
+
+		let times = PrayerManager::new(
+			CalculationMethods::MWL,
+			Some(HightLatMethods::AqrabAlBilad(48.5)),
+		)
+		.get_times(a_date, a_house);
+		let night = times.sunrise + 24.0 - times.sunset;
+		let reference_night = reference.sunrise + 24.0 - reference.sunset;
+		assert!(
+			((times.sunrise - times.fajr) / night
+				- (reference.sunrise - reference.fajr) / reference_night)
+				.abs()
+				< 1e-9
+		);
+		assert!(
+			((times.isha - times.sunset) / night - (reference.isha - reference.sunset) / reference_night)
+				.abs()
+				< 1e-9
+		);
+
+		let times = PrayerManager::new(
+			CalculationMethods::MWL,
+			Some(HightLatMethods::NearestGoodDay),
+		)
+		.get_times(a_date, a_house);
+		let good_day = (1..)
+			.map(|days| without.get_times(a_date - Duration::days(days), a_house))
+			.find(|times| !times.fajr.is_nan())
+			.unwrap();
+		assert_eq!(times.fajr, good_day.fajr);
+		assert_eq!(times.status(Prayer::Fajr), TimeStatus::Adjusted);
+
+		// times the sun reaches are kept
+		let a_date = Utc.ymd(2021, 4, 12);
+		assert_eq!(
+			PrayerManager::new(
+				CalculationMethods::MWL,
+				Some(HightLatMethods::NearestGoodDay)
+			)
+			.get_times(a_date, a_house),
+			without.get_times(a_date, a_house)
+		);
+	}
+
+	#[test]
+	fn synthesize_polar_times() {
+		let tromso = Coordinates(69.6492047, 18.9553238, 0.0);
+		let svalbard = Coordinates(78.2231722, 15.6267229, 0.0);
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::OneSeventh));
+
+		// polar night
+		let a_date = Utc.ymd(2021, 12, 21);
+		let times = prayer_manager.get_times(a_date, tromso);
+		assert!(times.sunrise.is_nan() && times.sunset.is_nan());
+		assert_eq!(times.status(Prayer::Sunrise), TimeStatus::Undefined);
+		// no shadow of asr without the sun
+		assert!(times.asr.is_nan());
+		assert_ne!(
+			prayer_manager
+				.current_prayer(a_date.and_hms(11, 30, 0), tromso)
+				.map(|current| current.prayer),
+			Some(Prayer::Asr)
+		);
+
+		let times = prayer_manager
+			.with_polar(PolarMethods::NearestDay)
+			.get_times(a_date, tromso);
+		assert_eq!(times.status(Prayer::Sunrise), TimeStatus::Synthesized);
+		assert_eq!(times.status(Prayer::Asr), TimeStatus::Synthesized);
+		assert_eq!(times.status(Prayer::Sunset), TimeStatus::Synthesized);
+		assert_eq!(times.status(Prayer::Maghrib), TimeStatus::Synthesized);
+		assert_eq!(times.status(Prayer::Midnight), TimeStatus::Synthesized);
+		assert_eq!(times.status(Prayer::Dhuhr), TimeStatus::Computed);
+		assert!(times.fajr < times.sunrise);
+		assert!(times.sunrise < times.dhuhr && times.dhuhr < times.asr);
+		assert!(times.asr < times.sunset && times.sunset < times.isha);
+
+		// sunrise, asr and sunset all come from the nearest day with the three of them
+		let reference = (1..=183)
+			.flat_map(|days| [a_date - Duration::days(days), a_date + Duration::days(days)])
+			.map(|day| prayer_manager.get_times(day, tromso))
+			.find(|times| !(times.sunrise.is_nan() || times.asr.is_nan() || times.sunset.is_nan()))
+			.unwrap();
+		for prayer in [Prayer::Sunrise, Prayer::Asr, Prayer::Sunset].iter() {
+			let from_noon = times.get(*prayer) - times.dhuhr;
+			assert!((from_noon - (reference.get(*prayer) - reference.dhuhr)).abs() < 1e-3);
+		}
+
+		let times = prayer_manager
+			.with_polar(PolarMethods::ReferenceLatitude(65.0))
+			.get_times(a_date, tromso);
+		let reference = prayer_manager.get_times(a_date, Coordinates(65.0, tromso.1, tromso.2));
+		assert_eq!(times.sunrise, reference.sunrise);
+		assert_eq!(times.sunset, reference.sunset);
+		assert_eq!(times.asr, reference.asr);
+
+		// polar day
+		let a_date = Utc.ymd(2021, 6, 21);
+		let times = prayer_manager
+			.with_polar(PolarMethods::NearestDay)
+			.get_times(a_date, svalbard);
+		assert_eq!(times.status(Prayer::Sunset), TimeStatus::Synthesized);
+		assert_eq!(times.status(Prayer::Asr), TimeStatus::Computed);
+		assert_eq!(times.status(Prayer::Fajr), TimeStatus::Adjusted);
+		assert!(times.fajr < times.sunrise && times.sunset < times.isha);
+
+		// days with a sunrise and sunset are left untouched
+		let a_date = Utc.ymd(2021, 4, 12);
+		assert_eq!(
+			prayer_manager
+				.with_polar(PolarMethods::NearestDay)
+				.get_times(a_date, tromso),
+			prayer_manager.get_times(a_date, tromso)
+		);
+	}
+
+	#[test]
+	fn compute_minutes_based_prayer_times() {
+		let a_date = Utc.ymd(2021, 4, 12);
+		let a_house = Coordinates(21.4225, 39.8262, 277.0);
+
+		let times =
+			PrayerManager::new(CalculationMethods::Makkah(false), None).get_times(a_date, a_house);
+		assert_eq!(times.maghrib, times.sunset);
+		assert!((times.isha - times.maghrib - 1.5).abs() < 1e-9);
+		assert!((times.fajr - times.imsak - 10.0 / 60.0).abs() < 1e-9);
+
+		let times =
+			PrayerManager::new(CalculationMethods::Makkah(true), None).get_times(a_date, a_house);
+		assert!((times.isha - times.maghrib - 2.0).abs() < 1e-9);
+
+		let method = CalculationMethod::new(
+			None,
+			18.0,
+			None,
+			Some(CalculationType::Minutes(3.0)),
+			CalculationType::Minutes(75.0),
+			None,
+		);
+		let times =
+			PrayerManager::new(CalculationMethods::Custom(method), None).get_times(a_date, a_house);
+		assert!((times.maghrib - times.sunset - 3.0 / 60.0).abs() < 1e-9);
+		assert!((times.isha - times.maghrib - 75.0 / 60.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn validate_coordinates() {
+		let coords = Coordinates::new(38.8976763, -77.036529)
+			.and_then(|coords| coords.with_elevation(18.0))
+			.unwrap();
+		assert_eq!(coords, Coordinates(38.8976763, -77.036529, 18.0));
+		assert_eq!(coords.latitude(), 38.8976763);
+		assert_eq!(coords.longitude(), -77.036529);
+		assert_eq!(coords.elevation(), 18.0);
+
+		assert_eq!(Coordinates::new(0.0, 190.0).unwrap().longitude(), -170.0);
+		assert_eq!(Coordinates::new(0.0, -540.0).unwrap().longitude(), -180.0);
+		assert_eq!(
+			Coordinates::new(-91.0, 0.0),
+			Err(Error::InvalidLatitude(-91.0))
+		);
+		assert!(Coordinates::new(f64::NAN, 0.0).is_err());
+		assert!(Coordinates::new(0.0, f64::INFINITY).is_err());
+		assert!(Coordinates::new(0.0, 0.0)
+			.unwrap()
+			.with_elevation(f64::NAN)
+			.is_err());
+	}
+
+	#[test]
+	fn build_calculation_method() {
+		let method = CalculationMethod::builder()
+			.fajr(16.0)
+			.maghrib(CalculationType::Angle(4.0))
+			.isha(CalculationType::Angle(14.0))
+			.midnight(MidnightMethod::Jafari)
+			.build();
+		assert_eq!(
+			method,
+			Ok(PrayerManager::get_calculation_method(
+				CalculationMethods::Jafari
+			))
+		);
+
+		let method = CalculationMethod::builder()
+			.fajr(18.0)
+			.dhuhr(1.0)
+			.asr(AsrJuristic::Hanafi)
+			.isha(CalculationType::Minutes(90.0))
+			.high_lats(HightLatMethods::OneSeventh)
+			.build()
+			.unwrap();
+		assert_eq!(method.fajr(), 18.0);
+		assert_eq!(method.dhuhr(), 1.0);
+		assert_eq!(method.asr(), AsrJuristic::Hanafi);
+		assert_eq!(method.isha(), CalculationType::Minutes(90.0));
+		assert_eq!(method.imsak(), CalculationType::Minutes(10.0));
+		assert_eq!(method.high_lats(), Some(HightLatMethods::OneSeventh));
+		assert_eq!(
+			PrayerManager::new(CalculationMethods::Custom(method), None),
+			PrayerManager::new(
+				CalculationMethods::Custom(method),
+				Some(HightLatMethods::OneSeventh)
+			)
+		);
+
+		let builder = CalculationMethod::builder().fajr(18.0);
+		assert_eq!(builder.build(), Err(Error::MissingParameter("isha")));
+		assert_eq!(
+			builder.isha(CalculationType::Angle(95.0)).build(),
+			Err(Error::InvalidAngle("isha", 95.0))
+		);
+		assert_eq!(
+			builder
+				.isha(CalculationType::Angle(17.0))
+				.maghrib(CalculationType::Minutes(-3.0))
+				.build(),
+			Err(Error::InvalidMinutes("maghrib", -3.0))
+		);
+		assert!(CalculationMethod::builder()
+			.fajr(18.0)
+			.isha(CalculationType::Angle(17.0))
+			.tune(Tune {
+				sunrise: -7.0,
+				..Tune::default()
+			})
+			.build()
+			.is_ok());
+		let invalid = CalculationMethod::builder()
+			.fajr(18.0)
+			.isha(CalculationType::Angle(17.0))
+			.tune(Tune {
+				dhuhr: f64::NAN,
+				..Tune::default()
+			})
+			.build();
+		assert!(
+			matches!(invalid, Err(Error::InvalidMinutes("dhuhr tune", minutes)) if minutes.is_nan())
+		);
+		assert_eq!(
+			builder
+				.fajr(-18.0)
+				.isha(CalculationType::Angle(17.0))
+				.build(),
+			Err(Error::InvalidAngle("fajr", -18.0))
+		);
+	}
+
+	#[test]
+	fn compute_tuned_prayer_times() {
+		let a_date = Utc.ymd(2021, 4, 12);
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None);
+		let times = prayer_manager.get_times(a_date, a_house);
+
+		let tune = Tune {
+			fajr: -2.0,
+			dhuhr: 2.0,
+			maghrib: 3.0,
+			isha: 6.0,
+			..Tune::default()
+		};
+		let tuned = prayer_manager.with_tune(tune).get_times(a_date, a_house);
+
+		assert_eq!(tuned.imsak, times.imsak);
+		assert!((tuned.fajr - times.fajr + 2.0 / 60.0).abs() < 1e-9);
+		assert!((tuned.dhuhr - times.dhuhr - 2.0 / 60.0).abs() < 1e-9);
+		assert_eq!(tuned.sunset, times.sunset);
+		assert!((tuned.maghrib - times.maghrib - 3.0 / 60.0).abs() < 1e-9);
+		assert!((tuned.isha - times.isha - 6.0 / 60.0).abs() < 1e-9);
+		assert_eq!(tuned.midnight, times.midnight);
+
+		let method = PrayerManager::get_calculation_method(CalculationMethods::MWL).with_tune(tune);
+		assert_eq!(
+			PrayerManager::new(CalculationMethods::Custom(method), None).get_times(a_date, a_house),
+			tuned
+		);
+	}
+
+	#[test]
+	fn compute_refined_prayer_times() {
+		use crate::astronomy::{get_julian_day, sun_equatorial};
+		use crate::dmath;
+
+		let a_date = Utc.ymd(2021, 2, 1);
+		let a_house = Coordinates(59.9138688, 10.7522454, 0.0);
+		let single = PrayerManager::new(CalculationMethods::MWL, None).get_times(a_date, a_house);
+		let times = PrayerManager::new(CalculationMethods::MWL, None)
+			.with_iterations(Iterations::Converge {
+				tolerance: 0.01,
+				max: 10,
+			})
+			.get_times(a_date, a_house);
+
+		// altitude of the sun at a UTC time of the day
+		let altitude = |hours: f64| {
+			let (decl, eqt, _) = sun_equatorial(get_julian_day(&a_date) + hours / 24.0);
+			let hour_angle = 15.0 * (hours + a_house.1 / 15.0 + eqt - 12.0);
+			dmath::arcsin(
+				&(dmath::sin(&a_house.0) * dmath::sin(&decl)
+					+ dmath::cos(&a_house.0) * dmath::cos(&decl) * dmath::cos(&hour_angle)),
+			)
+		};
+
+		assert!((altitude(times.fajr) + 18.0).abs() < 1e-4);
+		assert!((altitude(times.isha) + 17.0).abs() < 1e-4);
+		assert!((altitude(single.fajr) + 18.0).abs() > 1e-3);
+
+		let twice = PrayerManager::new(CalculationMethods::MWL, None)
+			.with_iterations(Iterations::Fixed(2))
+			.get_times(a_date, a_house);
+		assert_ne!(twice.fajr, single.fajr);
+	}
+
+	#[test]
+	fn resolve_regional_calculation_methods() {
+		let angles = |method: CalculationMethods| {
+			let method = PrayerManager::get_calculation_method(method);
+			(method.fajr(), method.isha())
+		};
+
+		assert_eq!(
+			angles(CalculationMethods::Gulf),
+			(19.5, CalculationType::Minutes(90.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Kuwait),
+			(18.0, CalculationType::Angle(17.5))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Qatar),
+			(18.0, CalculationType::Minutes(90.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Singapore),
+			(20.0, CalculationType::Angle(18.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Malaysia),
+			(20.0, CalculationType::Angle(18.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Indonesia),
+			(20.0, CalculationType::Angle(18.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Turkey),
+			(18.0, CalculationType::Angle(17.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Dubai),
+			(18.2, CalculationType::Angle(18.2))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Russia),
+			(16.0, CalculationType::Angle(15.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Algeria),
+			(18.0, CalculationType::Angle(17.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Tunisia),
+			(18.0, CalculationType::Angle(18.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Morocco),
+			(19.0, CalculationType::Angle(17.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Jordan),
+			(18.0, CalculationType::Angle(18.0))
+		);
+		assert_eq!(
+			angles(CalculationMethods::Portugal),
+			(18.0, CalculationType::Minutes(77.0))
+		);
+
+		let a_date = Utc.ymd(2021, 4, 12);
+
+		// Lisbon: maghrib 3 minutes after sunset, isha 77 minutes after maghrib
+		let a_house = Coordinates(38.7222524, -9.1393366, 0.0);
+		let times = PrayerManager::new(CalculationMethods::Portugal, None).get_times(a_date, a_house);
+		assert!((times.maghrib - times.sunset - 3.0 / 60.0).abs() < 1e-9);
+		assert!((times.isha - times.maghrib - 77.0 / 60.0).abs() < 1e-9);
+
+		// Amman: maghrib 5 minutes after sunset
+		let a_house = Coordinates(31.9539494, 35.910635, 0.0);
+		let times = PrayerManager::new(CalculationMethods::Jordan, None).get_times(a_date, a_house);
+		assert!((times.maghrib - times.sunset - 5.0 / 60.0).abs() < 1e-9);
+
+		// Istanbul: Diyanet's fixed offsets on top of the 18°/17° angles
+		let a_house = Coordinates(41.0082376, 28.9783589, 0.0);
+		let times = PrayerManager::new(CalculationMethods::Turkey, None).get_times(a_date, a_house);
+		let angles = PrayerManager::new(
+			CalculationMethods::Custom(CalculationMethod::from(18.0, CalculationType::Angle(17.0))),
+			None,
+		)
+		.get_times(a_date, a_house);
+		assert_eq!(times.fajr, angles.fajr);
+		assert!((times.sunrise - angles.sunrise + 7.0 / 60.0).abs() < 1e-9);
+		assert!((times.dhuhr - angles.dhuhr - 5.0 / 60.0).abs() < 1e-9);
+		assert!((times.asr - angles.asr - 4.0 / 60.0).abs() < 1e-9);
+		assert!((times.maghrib - angles.maghrib - 7.0 / 60.0).abs() < 1e-9);
+		assert_eq!(times.isha, angles.isha);
+	}
+
+	#[test]
+	fn parse_calculation_methods() {
+		assert_eq!("MWL".parse(), Ok(CalculationMethods::MWL));
+		assert_eq!("isna".parse(), Ok(CalculationMethods::ISNA));
+		assert_eq!(
+			"Makkah-Ramadan".parse(),
+			Ok(CalculationMethods::Makkah(true))
+		);
+		assert_eq!("umm_al_qura".parse(), Ok(CalculationMethods::Makkah(false)));
+		assert_eq!("Jafari".parse(), Ok(CalculationMethods::Jafari));
+		assert_eq!(
+			"moonsighting-ahmer".parse(),
+			Ok(CalculationMethods::MoonsightingCommittee(Shafaq::Ahmer))
+		);
+		assert_eq!(
+			"Nope".parse::<CalculationMethods>(),
+			Err(Error::UnknownName("calculation method", "Nope".to_string()))
+		);
+
+		let methods = [
+			CalculationMethods::MWL,
+			CalculationMethods::Makkah(false),
+			CalculationMethods::Makkah(true),
+			CalculationMethods::MF,
+			CalculationMethods::Turkey,
+			CalculationMethods::MoonsightingCommittee(Shafaq::General),
+			CalculationMethods::MoonsightingCommittee(Shafaq::Abyad),
+		];
+		for method in methods.iter() {
+			assert_eq!(method.to_string().parse(), Ok(*method));
+			assert!(method.region().is_some());
+		}
+		assert_eq!(
+			CalculationMethods::Makkah(true).to_string(),
+			"Makkah-Ramadan"
+		);
+		assert_eq!(CalculationMethods::MWL.full_name(), "Muslim World League");
+
+		assert_eq!("hanafi".parse(), Ok(AsrJuristic::Hanafi));
+		assert_eq!(AsrJuristic::Hanafi.to_string(), "Hanafi");
+		assert_eq!("JAFARI".parse(), Ok(MidnightMethod::Jafari));
+		assert_eq!("one seventh".parse(), Ok(HightLatMethods::OneSeventh));
+		assert_eq!(
+			"nearest-latitude".parse(),
+			Ok(HightLatMethods::NearestLatitude(48.5))
+		);
+		assert_eq!(
+			HightLatMethods::NearestLatitude(50.0).to_string().parse(),
+			Ok(HightLatMethods::NearestLatitude(50.0))
+		);
+		assert_eq!(
+			"angle-based:45".parse::<HightLatMethods>(),
+			Err(Error::UnknownName(
+				"high latitudes method",
+				"angle-based:45".to_string()
+			))
+		);
+		assert_eq!(
+			PolarMethods::ReferenceLatitude(60.0).to_string().parse(),
+			Ok(PolarMethods::ReferenceLatitude(60.0))
+		);
+		assert_eq!(
+			"reference latitude".parse(),
+			Ok(PolarMethods::ReferenceLatitude(65.0))
+		);
+	}
+
+	#[test]
+	fn parse_custom_calculation_methods() {
+		let method = CalculationMethod::builder()
+			.fajr(18.0)
+			.isha(CalculationType::Minutes(90.0))
+			.maghrib(CalculationType::Angle(4.0))
+			.build()
+			.unwrap();
+		assert_eq!(
+			"fajr=18,isha=90min,maghrib=4deg".parse(),
+			Ok(CalculationMethods::Custom(method))
+		);
+
+		let method = CalculationMethod::builder()
+			.imsak(CalculationType::Angle(19.5))
+			.fajr(19.5)
+			.dhuhr(2.0)
+			.asr(AsrJuristic::Hanafi)
+			.isha(CalculationType::Angle(17.5))
+			.midnight(MidnightMethod::Jafari)
+			.high_lats(HightLatMethods::AqrabAlBilad(45.0))
+			.moonsighting(Shafaq::Ahmer)
+			.build()
+			.unwrap();
+		let custom = CalculationMethods::Custom(method);
+		assert_eq!(
+			custom.to_string(),
+			"imsak=19.5,fajr=19.5,dhuhr=2min,asr=Hanafi,maghrib=0min,isha=17.5,midnight=Jafari,high-lats=AqrabAlBilad:45,shafaq=Ahmer"
+		);
+		assert_eq!(custom.to_string().parse(), Ok(custom));
+
+		// the tune of a method is kept
+		let diyanet = CalculationMethods::Custom(PrayerManager::get_calculation_method(
+			CalculationMethods::Turkey,
+		));
+		assert!(diyanet
+			.to_string()
+			.ends_with(",tune.sunrise=-7min,tune.dhuhr=5min,tune.asr=4min,tune.maghrib=7min"));
+		assert_eq!(diyanet.to_string().parse(), Ok(diyanet));
+		assert_eq!(
+			"fajr=18,isha=17,tune.isha=-2.5"
+				.parse::<CalculationMethods>()
+				.map(|method| match method {
+					CalculationMethods::Custom(method) => method.tune().isha,
+					_ => 0.0,
+				}),
+			Ok(-2.5)
+		);
+		assert_eq!(
+			"fajr=18,isha=17,tune.noon=5".parse::<CalculationMethods>(),
+			Err(Error::UnknownName("prayer", "noon".to_string()))
+		);
+		assert_eq!(custom.region(), None);
+
+		assert_eq!(
+			"isha=17".parse::<CalculationMethods>(),
+			Err(Error::MissingParameter("fajr"))
+		);
+		assert_eq!(
+			"fajr=18min,isha=17".parse::<CalculationMethods>(),
+			Err(Error::InvalidValue("fajr", "18min".to_string()))
+		);
+		assert_eq!(
+			"fajr=95,isha=17".parse::<CalculationMethods>(),
+			Err(Error::InvalidAngle("fajr", 95.0))
+		);
+		assert_eq!(
+			"fajr=18,isha=17,sunrise=1".parse::<CalculationMethods>(),
+			Err(Error::UnknownName("parameter", "sunrise".to_string()))
+		);
+	}
+
+	#[test]
+	fn compute_moonsighting_committee_prayer_times() {
+		let prayer_manager = PrayerManager::new(
+			CalculationMethods::MoonsightingCommittee(Shafaq::General),
+			None,
+		);
+
+		// Raleigh, reference times from the Adhan library (rounded to the minute, UTC), which
+		// uses a more precise solar ephemeris
+		let a_date = Utc.ymd(2016, 1, 31);
+		let a_house = Coordinates(35.7750, -78.6336, 0.0);
+		let times = prayer_manager.get_times(a_date, a_house);
+		let near =
+			|time: f64, hours: f64, minutes: f64| (time - hours - minutes / 60.0).abs() < 2.0 / 60.0;
+
+		assert!(near(times.fajr, 10.0, 48.0));
+		assert!(near(times.sunrise, 12.0, 16.0));
+		assert!(near(times.dhuhr, 17.0, 33.0));
+		assert!(near(times.asr, 20.0, 20.0));
+		assert!(near(times.maghrib, 22.0, 43.0));
+		assert!(near(times.isha, 24.0, 5.0));
+
+		// the seasonal twilight is later than 18° in winter
+		let angles = PrayerManager::new(
+			CalculationMethods::Custom(CalculationMethod::from(18.0, CalculationType::Angle(18.0))),
+			None,
+		)
+		.get_times(a_date, a_house);
+		assert!(times.isha < angles.isha);
+		assert_eq!(times.status(Prayer::Isha), TimeStatus::Computed);
+
+		let abyad = PrayerManager::new(
+			CalculationMethods::MoonsightingCommittee(Shafaq::Abyad),
+			None,
+		)
+		.get_times(a_date, a_house);
+		let ahmer = PrayerManager::new(
+			CalculationMethods::MoonsightingCommittee(Shafaq::Ahmer),
+			None,
+		)
+		.get_times(a_date, a_house);
+		assert!(ahmer.isha < times.isha && times.isha <= abyad.isha);
+
+		// one seventh of the night above 55°
+		let a_date = Utc.ymd(2021, 6, 21);
+		let a_house = Coordinates(59.9138688, 10.7522454, 0.0);
+		let times = prayer_manager.get_times(a_date, a_house);
+		let night = times.sunrise + 24.0 - times.sunset;
+		assert!((times.sunrise - times.fajr - night / 7.0).abs() < 1e-9);
+		assert!((times.isha - times.sunset - night / 7.0).abs() < 1e-9);
+		assert_eq!(times.status(Prayer::Fajr), TimeStatus::Adjusted);
+	}
+
+	#[test]
+	fn compute_timetable() {
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+
+		let timetable = prayer_manager.timetable(Utc.ymd(2021, 2, 1), Utc.ymd(2021, 3, 31), a_house);
+		assert_eq!(timetable.size_hint(), (59, Some(59)));
+		let days: Vec<_> = timetable.collect();
+		assert_eq!(days.len(), 59);
+		assert_eq!(days[0].0, Utc.ymd(2021, 2, 1));
+		assert_eq!(days[58].0, Utc.ymd(2021, 3, 31));
+		for (date, times) in days.iter() {
+			assert_eq!(*times, prayer_manager.get_date_times(*date, a_house));
+		}
+
+		let empty = prayer_manager.timetable(Utc.ymd(2021, 2, 1), Utc.ymd(2021, 1, 31), a_house);
+		assert_eq!(empty.count(), 0);
+
+		// starting from the previous day gives the same converged times
+		let refined = prayer_manager.with_iterations(Iterations::Converge {
+			tolerance: 0.01,
+			max: 10,
+		});
+		for (date, times) in refined.timetable(Utc.ymd(2021, 2, 1), Utc.ymd(2021, 3, 31), a_house) {
+			let single = refined.get_date_times(date, a_house);
+			for (prayer, time) in times.iter() {
+				let diff = time
+					.unwrap()
+					.signed_duration_since(single.get(prayer).unwrap());
+				assert!(diff.num_milliseconds().abs() <= 20);
+			}
+		}
+	}
+
+	#[test]
+	fn export_timetable() {
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+		let a_zone = FixedOffset::west(4 * 3600);
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let days =
+			|| prayer_manager.timetable(a_zone.ymd(2021, 4, 12), a_zone.ymd(2021, 4, 13), a_house);
+
+		let mut csv = Vec::new();
+		CsvWriter::new().write(&mut csv, days()).unwrap();
+		let csv = String::from_utf8(csv).unwrap();
+		let mut lines = csv.lines();
+		assert_eq!(
+			lines.next(),
+			Some("date,imsak,fajr,sunrise,dhuhr,asr,sunset,maghrib,isha,midnight")
+		);
+		assert_eq!(
+			lines.next(),
+			Some("2021-04-12,04:52,05:02,06:35,13:09,16:50,19:43,19:43,21:11,01:09")
+		);
+		assert_eq!(lines.count(), 1);
+
+		let mut csv = Vec::new();
+		CsvWriter::new()
+			.with_columns(&[Prayer::Fajr, Prayer::Isha])
+			.with_clock(Clock::H12)
+			.write(&mut csv, days())
+			.unwrap();
+		let csv = String::from_utf8(csv).unwrap();
+		assert_eq!(csv.lines().nth(1), Some("2021-04-12,5:02 AM,9:11 PM"));
+
+		// undefined times
+		let oslo = Coordinates(59.9138688, 10.7522454, 0.0);
+		let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None);
+		let summer = prayer_manager.timetable(Utc.ymd(2021, 6, 21), Utc.ymd(2021, 6, 21), oslo);
+		let mut json = Vec::new();
+		JsonWriter::new()
+			.with_columns(&[Prayer::Fajr, Prayer::Sunrise])
+			.write(&mut json, summer)
+			.unwrap();
+		let json = String::from_utf8(json).unwrap();
+		assert_eq!(
+			json,
+			"[\n  {\"date\": \"2021-06-21\", \"fajr\": null, \"sunrise\": \"2021-06-21T01:53:49+00:00\"}\n]\n"
+		);
+
+		let mut json = Vec::new();
+		JsonWriter::new().write(&mut json, days()).unwrap();
+		let json = String::from_utf8(json).unwrap();
+		assert!(json.contains("\"isha\": \"2021-04-12T21:11:"));
+		assert_eq!(json.matches("\"date\"").count(), 2);
+	}
+
+	#[test]
+	fn export_calendar() {
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+		let a_zone = FixedOffset::west(4 * 3600);
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let days =
+			|| prayer_manager.timetable(a_zone.ymd(2021, 4, 12), a_zone.ymd(2021, 4, 13), a_house);
+
+		let mut ics = Vec::new();
+		IcalWriter::new().write(&mut ics, days()).unwrap();
+		let ics = String::from_utf8(ics).unwrap();
+		assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+		assert!(ics.ends_with("END:VCALENDAR\r\n"));
+		assert_eq!(ics.matches("BEGIN:VEVENT").count(), 10);
+		assert!(ics.contains("UID:20210412-fajr-38.8977_-77.0365@praye.rs\r\n"));
+		assert!(ics.contains("DTSTART:20210412T090200Z\r\nSUMMARY:Fajr\r\n"));
+		// isha of the 12th is after midnight UTC
+		assert!(ics.contains("DTSTART:20210413T011100Z\r\nSUMMARY:Isha\r\n"));
+		assert!(!ics.contains("VALARM"));
+		assert!(!ics.contains("DURATION"));
+
+		// the same events, with alarms and durations
+		let mut again = Vec::new();
+		IcalWriter::new()
+			.with_prayers(&[Prayer::Sunrise])
+			.with_alarm(20)
+			.with_duration(10)
+			.write(&mut again, days())
+			.unwrap();
+		let again = String::from_utf8(again).unwrap();
+		assert_eq!(again.matches("BEGIN:VEVENT").count(), 2);
+		assert!(again.contains("UID:20210413-sunrise-38.8977_-77.0365@praye.rs\r\n"));
+		assert!(again.contains("DURATION:PT10M\r\nSUMMARY:Sunrise\r\n"));
+		assert!(again.contains(
+			"BEGIN:VALARM\r\nACTION:DISPLAY\r\nDESCRIPTION:Sunrise\r\nTRIGGER:-PT20M\r\nEND:VALARM\r\n"
+		));
+		assert!(again.lines().all(|line| line.len() <= 75));
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn serialize_prayer_times() {
+		use serde_json::json;
+
+		fn round_trip<T>(value: &T) -> T
+		where
+			T: serde::Serialize + serde::de::DeserializeOwned,
+		{
+			serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+		}
+
+		let a_date = Utc.ymd(2021, 6, 21);
+		let london = Coordinates(51.5073509, -0.1277583, 0.0);
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+		let times = prayer_manager.get_times(a_date, london);
+		assert_eq!(
+			serde_json::to_value(times).unwrap(),
+			json!({
+				"imsak": times.imsak,
+				"fajr": times.fajr,
+				"sunrise": times.sunrise,
+				"dhuhr": times.dhuhr,
+				"asr": times.asr,
+				"sunset": times.sunset,
+				"maghrib": times.maghrib,
+				"isha": times.isha,
+				"midnight": times.midnight,
+				"status": [
+					"Adjusted", "Adjusted", "Computed", "Computed", "Computed",
+					"Computed", "Computed", "Adjusted", "Computed"
+				],
+			})
+		);
+		assert_eq!(round_trip(&times), times);
+
+		// timestamps as RFC 3339 strings, undefined times as null
+		let tromso = Coordinates(69.6492047, 18.9553238, 0.0);
+		let times = PrayerManager::new(CalculationMethods::MWL, None)
+			.get_date_times(Utc.ymd(2021, 12, 21), tromso);
+		let value = serde_json::to_value(times).unwrap();
+		assert_eq!(value["sunrise"], json!(null));
+		assert_eq!(
+			value["status"][Prayer::Sunrise as usize],
+			json!("Undefined")
+		);
+		assert_eq!(
+			value["dhuhr"],
+			json!(times
+				.dhuhr
+				.unwrap()
+				.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+		);
+		assert_eq!(round_trip(&times), times);
+		let times =
+			prayer_manager.get_local_times(a_date.naive_utc(), &FixedOffset::east(3600), london);
+		assert_eq!(round_trip(&times), times);
+
+		assert_eq!(
+			serde_json::to_value(london).unwrap(),
+			json!([51.5073509, -0.1277583, 0.0])
+		);
+		assert_eq!(round_trip(&london), london);
+		assert_eq!(
+			serde_json::to_value(Prayer::Maghrib).unwrap(),
+			json!("Maghrib")
+		);
+
+		let custom = CalculationMethod::builder()
+			.fajr(18.0)
+			.isha(CalculationType::Minutes(90.0))
+			.maghrib(CalculationType::Angle(4.0))
+			.asr(AsrJuristic::Hanafi)
+			.high_lats(HightLatMethods::AqrabAlBilad(45.0))
+			.build()
+			.unwrap();
+		assert_eq!(round_trip(&custom), custom);
+		assert_eq!(
+			serde_json::to_value(CalculationMethods::MWL).unwrap(),
+			json!("MWL")
+		);
+		assert_eq!(
+			serde_json::to_value(CalculationMethods::Makkah(true)).unwrap(),
+			json!({ "Makkah": true })
+		);
+		for method in [
+			CalculationMethods::MWL,
+			CalculationMethods::Makkah(true),
+			CalculationMethods::MoonsightingCommittee(Shafaq::Ahmer),
+			CalculationMethods::Custom(custom),
+		]
+		.iter()
+		{
+			assert_eq!(round_trip(method), *method);
+		}
+	}
+
+	#[test]
+	fn iterate_prayer_times() {
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let times = prayer_manager.get_times(Utc.ymd(2021, 4, 12), a_house);
+
+		assert_eq!(times.get(Prayer::Imsak), &times.imsak);
+		assert_eq!(times.get(Prayer::Sunset), &times.sunset);
+		assert_eq!(times.get(Prayer::Midnight), &times.midnight);
+
+		let prayers: Vec<Prayer> = times.iter().map(|(prayer, _)| prayer).collect();
+		assert_eq!(prayers, Prayer::ALL.to_vec());
+
+		let hours: Vec<f64> = times.iter().map(|(_, time)| *time).collect();
+		assert!(hours.windows(2).all(|pair| pair[0] <= pair[1]));
+		for (prayer, time) in times.iter() {
+			assert_eq!(time, times.get(prayer));
+		}
+
+		// with the Jafari midnight, the middle of the night can come before isha
+		let prayer_manager = PrayerManager::new(CalculationMethods::Tehran, None);
+		let london = Coordinates(51.5073509, -0.1277583, 0.0);
+		let times = prayer_manager.get_date_times(Utc.ymd(2021, 5, 22), london);
+		assert!(times.midnight < times.isha);
+		let prayers: Vec<Prayer> = times.iter().map(|(prayer, _)| prayer).collect();
+		assert_eq!(&prayers[7..], &[Prayer::Midnight, Prayer::Isha]);
+		let times: Vec<_> = times.iter().map(|(_, time)| time.unwrap()).collect();
+		assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+
+		// undefined times last
+		let times =
+			PrayerManager::new(CalculationMethods::MWL, None).get_times(Utc.ymd(2021, 6, 21), london);
+		let prayers: Vec<Prayer> = times.iter().map(|(prayer, _)| prayer).collect();
+		assert_eq!(&prayers[6..], &[Prayer::Imsak, Prayer::Fajr, Prayer::Isha]);
+	}
+
+	#[test]
+	fn find_current_and_next_prayers() {
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let times = prayer_manager.get_date_times(Utc.ymd(2021, 4, 12), a_house);
+
+		let at = Utc.ymd(2021, 4, 12).and_hms(20, 0, 0);
+		let current = prayer_manager.current_prayer(at, a_house).unwrap();
+		let next = prayer_manager.next_prayer(at, a_house).unwrap();
+		assert_eq!(current.prayer, Prayer::Dhuhr);
+		assert_eq!(Some(current.start), times.dhuhr);
+		assert_eq!(next.prayer, Prayer::Asr);
+		assert_eq!(Some(next.start), times.asr);
+		assert_eq!(current.remaining, next.remaining);
+		assert_eq!(Some(at + next.remaining), times.asr);
+
+		// isha of the previous day, until fajr
+		let at = Utc.ymd(2021, 4, 12).and_hms(3, 0, 0);
+		let current = prayer_manager.current_prayer(at, a_house).unwrap();
+		let next = prayer_manager.next_prayer(at, a_house).unwrap();
+		assert_eq!(current.prayer, Prayer::Isha);
+		assert_eq!(
+			Some(current.start),
+			prayer_manager
+				.get_date_times(Utc.ymd(2021, 4, 11), a_house)
+				.isha
+		);
+		assert_eq!(next.prayer, Prayer::Fajr);
+		assert_eq!(Some(next.start), times.fajr);
+
+		// in the time zone of the instant
+		let a_zone = FixedOffset::west(4 * 3600);
+		let at = a_zone.ymd(2021, 4, 12).and_hms(23, 0, 0);
+		let current = prayer_manager.current_prayer(at, a_house).unwrap();
+		let next = prayer_manager.next_prayer(at, a_house).unwrap();
+		assert_eq!(current.prayer, Prayer::Isha);
+		assert_eq!(current.start.timezone(), a_zone);
+		assert_eq!(next.prayer, Prayer::Fajr);
+		assert_eq!(
+			next.start.date().naive_local(),
+			NaiveDate::from_ymd(2021, 4, 13)
+		);
+	}
+
+	#[test]
+	fn compute_local_prayer_times() {
+		let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None);
+		let a_house = Coordinates(48.856614, 2.3522219, 35.0);
+		let a_zone = FixedOffset::east(2 * 3600);
+
+		let a_date = NaiveDate::from_ymd(2021, 4, 12);
+		let times = prayer_manager.get_local_times(a_date, &a_zone, a_house);
+		let utc_times = prayer_manager.get_date_times(Utc.ymd(2021, 4, 12), a_house);
+
+		assert_eq!(times.fajr, utc_times.fajr.map(|t| t.with_timezone(&a_zone)));
+		assert_eq!(times.isha.unwrap().naive_local().date(), a_date);
+
+		// Line Islands, UTC+14, far east of its time zone meridian
+		let a_house = Coordinates(1.8721, -157.4278, 0.0);
+		let a_zone = FixedOffset::east(14 * 3600);
+		let times = prayer_manager.get_local_times(a_date, &a_zone, a_house);
+
+		assert_eq!(times.dhuhr.unwrap().naive_local().date(), a_date);
+	}
+
+	#[test]
+	fn compute_with_ephemeris() {
+		use super::{ElevationMethod, Ephemeris, Meeus, Usno};
+
+		// example 25.a and 28.a of Astronomical Algorithms: 1992 October 13.0 TD
+		let (decl, eqt, ra) = Meeus::new().with_delta_t(0.0).sun_position(2448908.5);
+		assert!((decl + 7.78507).abs() < 0.0001);
+		assert!((ra * 15.0 - 198.38083).abs() < 0.001);
+		assert!((eqt * 3600.0 - (13.0 * 60.0 + 42.7)).abs() < 1.0);
+		let delta_t = Meeus::new().delta_t(2459316.5);
+		assert!((65.0..75.0).contains(&delta_t));
+		assert_eq!(Meeus::new().julian_day(&Utc.ymd(2021, 5, 28)), 2459362.5);
+
+		let a_date = Utc.ymd(2021, 4, 12);
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+		let times = prayer_manager.get_times(a_date, a_house);
+		assert_eq!(
+			prayer_manager
+				.with_ephemeris(Usno)
+				.get_times(a_date, a_house),
+			times
+		);
+
+		// sunrise and sunset (UT) at Brasilia (W047 51, S15 48) from the "Rise and Set for the Sun
+		// for 2020" table of the U.S. Naval Observatory, given to the minute; the julian day of
+		// Usno is a day ahead of the actual one on the first four dates
+		let brasilia = Coordinates(-15.8, -47.85, 0.0);
+		let precise = PrayerManager::new(CalculationMethods::MWL, None)
+			.with_elevation(ElevationMethod::Ignore)
+			.with_ephemeris(Meeus::new());
+		for (month, day, sunrise, sunset) in [
+			(1, 25, "08:57", "21:50"),
+			(2, 15, "09:07", "21:44"),
+			(7, 5, "09:40", "20:53"),
+			(12, 5, "08:31", "21:33"),
+			(4, 15, "09:19", "21:03"),
+			(10, 1, "08:54", "21:08"),
+		]
+		.iter()
+		{
+			let a_date = Utc.ymd(2020, *month, *day);
+			let times = precise.get_date_times(a_date, brasilia);
+			for (time, expected) in [(times.sunrise, sunrise), (times.sunset, sunset)].iter() {
+				let expected = a_date.and_time(expected.parse().unwrap()).unwrap();
+				let diff = time.unwrap().signed_duration_since(expected);
+				assert!(diff.num_seconds().abs() <= 60, "{} {}", a_date, diff);
+			}
+		}
+		for (month, day) in [(1, 25), (2, 15), (7, 5), (12, 5)].iter() {
+			let a_date = Utc.ymd(2020, *month, *day);
+			assert_eq!(
+				Usno.julian_day(&a_date),
+				Meeus::new().julian_day(&a_date) + 1.0
+			);
+		}
+
+		// the sun and the Qibla, with the default or another ephemeris
+		use super::{
+			kaaba_transits, kaaba_transits_with, qibla_times, qibla_times_with, solar_position,
+			solar_position_with,
+		};
+		let noon = a_date.and_hms(17, 9, 0);
+		assert_eq!(
+			solar_position_with(&Usno, a_house, noon),
+			solar_position(a_house, noon)
+		);
+		let sun = solar_position_with(&Meeus::new(), a_house, noon);
+		assert!((sun.azimuth - solar_position(a_house, noon).azimuth).abs() < 0.01);
+		assert_eq!(
+			qibla_times_with(&Usno, a_house, a_date),
+			qibla_times(a_house, a_date)
+		);
+		let times = qibla_times_with(&Meeus::new(), a_house, a_date);
+		assert_eq!(times.len(), 1);
+		assert!(
+			(times[0].time - qibla_times(a_house, a_date)[0].time)
+				.num_seconds()
+				.abs()
+				< 10
+		);
+		assert_eq!(kaaba_transits_with(&Usno, 2021), kaaba_transits(2021));
+		for (precise, transit) in kaaba_transits_with(&Meeus::new(), 2021)
+			.iter()
+			.zip(kaaba_transits(2021).iter())
+		{
+			assert_eq!(precise.date_naive(), transit.date_naive());
+		}
+	}
+
+	#[test]
+	fn compute_with_weather() {
+		use super::Observer;
+
+		let prayer_manager =
+			PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+		let a_date = Utc.ymd(2021, 4, 12);
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let times = prayer_manager.get_times(a_date, a_house);
+
+		// the standard atmosphere is the default
+		let standard = Observer::new(a_house)
+			.with_temperature(10.0)
+			.and_then(|observer| observer.with_pressure(1010.0))
+			.unwrap();
+		assert_eq!(prayer_manager.get_times(a_date, standard), times);
+		assert_eq!(
+			prayer_manager.get_times(a_date, Observer::from(a_house)),
+			times
+		);
+
+		// denser air raises the sun earlier and sets it later, by less than a minute
+		let cold = Observer::new(a_house)
+			.with_temperature(-20.0)
+			.and_then(|observer| observer.with_pressure(1040.0))
+			.unwrap();
+		let cold_times = prayer_manager.get_times(a_date, cold);
+		assert!((0.0..1.0 / 60.0).contains(&(times.sunrise - cold_times.sunrise)));
+		assert!((0.0..1.0 / 60.0).contains(&(cold_times.sunset - times.sunset)));
+		assert_eq!(cold_times.maghrib, cold_times.sunset);
+		assert_eq!(cold_times.fajr, times.fajr);
+		assert_eq!(cold_times.isha, times.isha);
+
+		// without air, no refraction
+		let vacuum = Observer::new(a_house).with_pressure(0.0).unwrap();
+		let vacuum_times = prayer_manager.get_times(a_date, vacuum);
+		assert!((0.0..4.0 / 60.0).contains(&(vacuum_times.sunrise - times.sunrise)));
+
+		assert_eq!(
+			Observer::new(a_house).with_temperature(-300.0),
+			Err(Error::InvalidTemperature(-300.0))
+		);
+		assert!(Observer::new(a_house).with_pressure(f64::NAN).is_err());
+	}
+
+	#[test]
+	fn compute_with_elevation() {
+		use super::ElevationMethod;
+
+		let prayer_manager = PrayerManager::new(CalculationMethods::Tehran, None);
+		let a_date = Utc.ymd(2021, 4, 12);
+		let tehran = Coordinates(35.6891975, 51.3889736, 1190.0);
+		let sea_level = Coordinates(tehran.0, tehran.1, 0.0);
+		let times = prayer_manager.get_times(a_date, tehran);
+		let with_elevation = |elevation| prayer_manager.with_elevation(elevation);
+
+		assert_eq!(
+			with_elevation(ElevationMethod::Dip).get_times(a_date, tehran),
+			times
+		);
+		assert_eq!(
+			with_elevation(ElevationMethod::Horizon(0.0)).get_times(a_date, tehran),
+			times
+		);
+		let ignored = with_elevation(ElevationMethod::Ignore).get_times(a_date, tehran);
+		assert_eq!(ignored, prayer_manager.get_times(a_date, sea_level));
+		assert_eq!(
+			with_elevation(ElevationMethod::Horizon(1190.0)).get_times(a_date, tehran),
+			ignored
+		);
+		assert_eq!(
+			with_elevation(ElevationMethod::Horizon(2000.0)).get_times(a_date, tehran),
+			ignored
+		);
+
+		let plateau = with_elevation(ElevationMethod::Horizon(1100.0)).get_times(a_date, tehran);
+		assert!(times.sunrise < plateau.sunrise && plateau.sunrise < ignored.sunrise);
+		assert!(times.sunset > plateau.sunset && plateau.sunset > ignored.sunset);
+		assert_eq!(plateau.fajr, times.fajr);
+		assert_eq!(plateau.maghrib, times.maghrib);
+
+		// the twilight angles are lowered as much as the horizon
+		let twilight = prayer_manager
+			.with_twilight_elevation(true)
+			.get_times(a_date, tehran);
+		assert_eq!(twilight.sunrise, times.sunrise);
+		assert!(twilight.fajr < times.fajr && twilight.isha > times.isha);
+		assert!(twilight.maghrib > times.maghrib);
+		assert_eq!(
+			prayer_manager
+				.with_twilight_elevation(true)
+				.get_times(a_date, sea_level),
+			prayer_manager.get_times(a_date, sea_level)
+		);
+
+		assert_eq!("dip".parse(), Ok(ElevationMethod::Dip));
+		assert_eq!(
+			ElevationMethod::Horizon(1100.0).to_string().parse(),
+			Ok(ElevationMethod::Horizon(1100.0))
+		);
+		assert_eq!(
+			"horizon".parse::<ElevationMethod>(),
+			Err(Error::MissingParameter("horizon elevation"))
+		);
+	}
+
+	#[test]
+	fn compute_solar_position() {
+		use super::solar_position;
+
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+
+		// extremes of the equation of time and the declination
+		let sun = solar_position(a_house, Utc.ymd(2021, 11, 3).and_hms(12, 0, 0));
+		assert!((sun.equation_of_time - 16.4).abs() < 0.1);
+		let sun = solar_position(a_house, Utc.ymd(2021, 2, 11).and_hms(12, 0, 0));
+		assert!((sun.equation_of_time + 14.2).abs() < 0.1);
+		let sun = solar_position(a_house, Utc.ymd(2021, 6, 21).and_hms(3, 32, 0));
+		assert!((sun.declination - 23.44).abs() < 0.01);
+		let sun = solar_position(a_house, Utc.ymd(2021, 3, 20).and_hms(9, 37, 0));
+		assert!(sun.declination.abs() < 0.01);
+		assert!(sun.right_ascension.min(24.0 - sun.right_ascension) < 0.01);
+
+		// at the mid-day, the sun is due south at its highest
+		let noon = Utc.ymd(2021, 6, 21).and_hms(17, 10, 0);
+		let sun = solar_position(a_house, noon);
+		assert!(sun.hour_angle.abs() < 0.5);
+		assert!((sun.azimuth - 180.0).abs() < 1.0);
+		assert!((sun.altitude - (90.0 - a_house.0 + sun.declination)).abs() < 0.01);
+		let before = solar_position(a_house, noon - Duration::hours(3));
+		let after = solar_position(a_house, noon + Duration::hours(3));
+		assert!(before.hour_angle < 0.0 && before.azimuth < 180.0);
+		assert!(after.hour_angle > 0.0 && after.azimuth > 180.0);
+		assert!(before.altitude < sun.altitude && after.altitude < sun.altitude);
+
+		let night = solar_position(a_house, noon + Duration::hours(12));
+		assert!(night.altitude < 0.0);
+		assert_eq!(night.shadow_ratio(), None);
+	}
+
+	#[test]
+	fn compute_qibla() {
+		use super::{qibla, KAABA};
+
+		// published bearings
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		assert!((qibla(a_house).bearing.unwrap() - 56.56).abs() < 0.01);
+		let paris = Coordinates(48.856614, 2.3522219, 35.0);
+		assert!((qibla(paris).bearing.unwrap() - 119.16).abs() < 0.01);
+		let jakarta = Coordinates(-6.2087634, 106.845599, 0.0);
+		assert!((qibla(jakarta).bearing.unwrap() - 295.15).abs() < 0.01);
+
+		let at_kaaba = qibla(KAABA);
+		assert_eq!(at_kaaba.bearing, None);
+		assert_eq!(at_kaaba.rhumb_bearing, None);
+		assert_eq!(at_kaaba.distance, 0.0);
+
+		let antipode = qibla(Coordinates(-KAABA.0, KAABA.1 - 180.0, 0.0));
+		assert_eq!(antipode.bearing, None);
+		assert!((antipode.distance - 20015.1).abs() < 0.1);
+		assert!(antipode.rhumb_bearing.is_some());
+
+		let north_pole = qibla(Coordinates(90.0, 0.0, 0.0));
+		assert!((north_pole.bearing.unwrap() - (180.0 - KAABA.1)).abs() < 1e-9);
+		assert_eq!(north_pole.rhumb_bearing, Some(180.0));
+		let south_pole = qibla(Coordinates(-90.0, 0.0, 0.0));
+		assert!((south_pole.bearing.unwrap() - KAABA.1).abs() < 1e-9);
+		assert_eq!(south_pole.rhumb_bearing, Some(0.0));
+
+		// due east and west on the same latitude
+		let east = qibla(Coordinates(KAABA.0, 0.0, 0.0));
+		assert!((east.rhumb_bearing.unwrap() - 90.0).abs() < 1e-9);
+		assert!(east.bearing.unwrap() < 90.0);
+		let west = qibla(Coordinates(KAABA.0, 100.0, 0.0));
+		assert!((west.rhumb_bearing.unwrap() - 270.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn compute_qibla_times() {
+		use super::solar_position;
+		use super::{kaaba_transits, qibla, qibla_times, QiblaDirection, Timelike, KAABA};
+
+		let check = |coords: Coordinates, date| {
+			let bearing = qibla(coords).bearing.unwrap();
+			let times = qibla_times(coords, date);
+			for qibla_time in times.iter() {
+				let sun = solar_position(coords, qibla_time.time);
+				let expected = match qibla_time.direction {
+					QiblaDirection::Sun => bearing,
+					QiblaDirection::Shadow => bearing + 180.0,
+				};
+				assert!(sun.altitude > 0.0);
+				assert!((crate::dmath::fix_angle(sun.azimuth - expected + 180.0) - 180.0).abs() < 0.01);
+			}
+			times
+		};
+
+		// the sun reaches the Qibla in the morning and its opposite in the evening in summer only
+		let paris = Coordinates(48.856614, 2.3522219, 35.0);
+		let times = check(paris, Utc.ymd(2021, 6, 21));
+		assert_eq!(times.len(), 2);
+		assert_eq!(times[0].direction, QiblaDirection::Sun);
+		assert_eq!(times[1].direction, QiblaDirection::Shadow);
+		assert!(check(paris, Utc.ymd(2021, 12, 21)).is_empty());
+
+		let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+		let times = check(a_house, Utc.ymd(2021, 12, 21));
+		assert_eq!(times.len(), 1);
+		assert_eq!(times[0].direction, QiblaDirection::Shadow);
+
+		// in the tropics, east of Makkah
+		let jakarta = Coordinates(-6.2087634, 106.845599, 0.0);
+		assert!(!check(jakarta, Utc.ymd(2021, 4, 12)).is_empty());
+
+		assert!(qibla_times(KAABA, Utc.ymd(2021, 4, 12)).is_empty());
+
+		let transits = kaaba_transits(2022);
+		assert_eq!(transits.len(), 2);
+		assert_eq!(transits[0].date(), Utc.ymd(2022, 5, 28));
+		assert_eq!((transits[0].hour(), transits[0].minute()), (9, 17));
+		assert_eq!(transits[1].date(), Utc.ymd(2022, 7, 15));
+		assert_eq!((transits[1].hour(), transits[1].minute()), (9, 26));
+
+		// no dates to search
+		assert!(kaaba_transits(i32::MAX).is_empty());
+		assert!(kaaba_transits(i32::MIN).is_empty());
+	}
+
+	#[cfg(feature = "chrono-tz")]
+	#[test]
+	fn compute_prayer_times_across_dst() {
+		use super::chrono_tz::Europe::Paris;
+		use super::Timelike;
+
+		let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None);
+		let a_house = Coordinates(48.856614, 2.3522219, 35.0);
+
+		let before = prayer_manager.get_local_times(NaiveDate::from_ymd(2021, 3, 27), &Paris, a_house);
+		let after = prayer_manager.get_local_times(NaiveDate::from_ymd(2021, 3, 28), &Paris, a_house);
+
+		assert_eq!(before.dhuhr.unwrap().hour(), 12);
+		assert_eq!(after.dhuhr.unwrap().hour(), 13);
+
+		let days: Vec<_> = prayer_manager
+			.timetable(Paris.ymd(2021, 3, 27), Paris.ymd(2021, 3, 28), a_house)
+			.collect();
+		assert_eq!(days[0].1, before);
+		assert_eq!(days[1].1, after);
+	}
 }
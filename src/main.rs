@@ -0,0 +1,618 @@
+// `chrono::Date` is deprecated upstream but remains the date type of the library
+#![allow(deprecated)]
+
+use chrono::SecondsFormat;
+use prayers::*;
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::process;
+use std::str::FromStr;
+
+const USAGE: &str = "\
+Print prayer times
+
+USAGE:
+    prayers --lat <degrees> --lon <degrees> [OPTIONS]
+
+OPTIONS:
+    --lat <degrees>        Latitude (-90 to 90)
+    --lon <degrees>        Longitude
+    --elevation <meters>   Elevation [default: 0]
+    --temperature <°C>     Air temperature, for the refraction at sunrise and sunset [default: 10]
+    --pressure <hPa>       Air pressure, for the refraction at sunrise and sunset [default: 1010]
+    --horizon <method>     How the elevation changes sunrise and sunset: Ignore, Dip (from the
+                           sea level) or Horizon:<meters> [default: Dip]
+    --twilight-elevation   Apply the elevation to the twilight angles too
+    --date <YYYY-MM-DD>    Day to print [default: today]
+    --month <YYYY-MM>      Print a month table
+    --from <YYYY-MM-DD>    Print a table from this day...
+    --to <YYYY-MM-DD>      ...to this day (inclusive)
+    --next                 Print the current and the next prayer
+    --method <name>        MWL, ISNA, Egypt, Makkah, Makkah-Ramadan, Karachi, Tehran, Jafari,
+                           MF, Gulf, Kuwait, Qatar, Singapore, Malaysia, Indonesia, Turkey,
+                           Dubai, Russia, Algeria, Tunisia, Morocco, Jordan, Portugal,
+                           Moonsighting, Moonsighting-Ahmer, Moonsighting-Abyad, or custom
+                           parameters such as fajr=18,isha=90min,maghrib=4deg [default: MWL]
+    --asr <juristic>       Standard or Hanafi [default: the one of the method]
+    --midnight <method>    Standard or Jafari [default: the one of the method]
+    --high-lats <method>   NightMiddle, AngleBased, OneSeventh, NearestLatitude[:<degrees>],
+                           NearestGoodDay or AqrabAlBilad[:<degrees>]
+                           [default: the one of the method]
+    --polar <method>       NearestDay or ReferenceLatitude[:<degrees>], for the days the sun does
+                           not rise or set [default: none]
+    --tz <zone>            local, UTC or an offset such as +03:00 (or an IANA name such as
+                           Europe/Paris when built with the chrono-tz feature) [default: local]
+    --format <format>      text, json or csv [default: text]
+    --12h                  Use a 12-hour clock (text and csv)
+    -h, --help             Print this message
+";
+
+/// What to print
+#[derive(PartialEq, Debug, Copy, Clone)]
+enum Span {
+	Day(Option<NaiveDate>),
+	Range(NaiveDate, NaiveDate),
+	Next,
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+enum Format {
+	Text,
+	Json,
+	Csv,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+enum Zone {
+	Local,
+	Fixed(FixedOffset),
+	#[cfg(feature = "chrono-tz")]
+	Iana(chrono_tz::Tz),
+}
+
+struct Options {
+	observer: Observer,
+	span: Span,
+	method: CalculationMethods,
+	asr: Option<AsrJuristic>,
+	midnight: Option<MidnightMethod>,
+	high_lats: Option<HightLatMethods>,
+	polar: Option<PolarMethods>,
+	elevation: ElevationMethod,
+	twilight_elevation: bool,
+	zone: Zone,
+	format: Format,
+	clock: Clock,
+}
+
+fn main() {
+	let args: Vec<String> = env::args().skip(1).collect();
+	if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+		print!("{}", USAGE);
+		return;
+	}
+
+	let options = match parse_args(&args) {
+		Ok(options) => options,
+		Err(message) => {
+			eprintln!("error: {}\n\n{}", message, USAGE);
+			process::exit(2);
+		}
+	};
+
+	let result = match options.zone.clone() {
+		Zone::Local => run(&options, chrono::Local),
+		Zone::Fixed(offset) => run(&options, offset),
+		#[cfg(feature = "chrono-tz")]
+		Zone::Iana(tz) => run(&options, tz),
+	};
+	if let Err(message) = result {
+		eprintln!("error: {}", message);
+		process::exit(1);
+	}
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+	let mut latitude = None;
+	let mut longitude = None;
+	let mut elevation = 0.0;
+	let mut temperature = None;
+	let mut pressure = None;
+	let mut date = None;
+	let mut month = None;
+	let mut from = None;
+	let mut to = None;
+	let mut next = false;
+	let mut method = CalculationMethods::MWL;
+	let mut asr = None;
+	let mut midnight = None;
+	let mut high_lats = None;
+	let mut polar = None;
+	let mut elevation_method = ElevationMethod::Dip;
+	let mut twilight_elevation = false;
+	let mut zone = Zone::Local;
+	let mut format = Format::Text;
+	let mut clock = Clock::H24;
+
+	let mut args = args.iter();
+	while let Some(arg) = args.next() {
+		// both `--name value` and `--name=value`
+		let (name, inline) = match arg.find('=') {
+			Some(index) if arg.starts_with("--") => (&arg[..index], Some(arg[index + 1..].to_string())),
+			_ => (arg.as_str(), None),
+		};
+		let mut value = || {
+			inline
+				.clone()
+				.or_else(|| args.next().cloned())
+				.ok_or_else(|| format!("missing value for {}", name))
+		};
+
+		match name {
+			"--lat" => latitude = Some(parse_number(name, &value()?)?),
+			"--lon" => longitude = Some(parse_number(name, &value()?)?),
+			"--elevation" => elevation = parse_number(name, &value()?)?,
+			"--temperature" => temperature = Some(parse_number(name, &value()?)?),
+			"--pressure" => pressure = Some(parse_number(name, &value()?)?),
+			"--date" => date = Some(parse_date(&value()?)?),
+			"--month" => month = Some(parse_date(&format!("{}-01", value()?))?),
+			"--from" => from = Some(parse_date(&value()?)?),
+			"--to" => to = Some(parse_date(&value()?)?),
+			"--next" => next = true,
+			"--method" => method = parse_name(&value()?)?,
+			"--asr" => asr = Some(parse_name(&value()?)?),
+			"--midnight" => midnight = Some(parse_name(&value()?)?),
+			"--high-lats" => high_lats = Some(parse_name(&value()?)?),
+			"--polar" => polar = Some(parse_name(&value()?)?),
+			"--horizon" => elevation_method = parse_name(&value()?)?,
+			"--twilight-elevation" => twilight_elevation = true,
+			"--tz" => zone = parse_zone(&value()?)?,
+			"--format" => format = parse_format(&value()?)?,
+			"--12h" => clock = Clock::H12,
+			_ => return Err(format!("unknown argument: {}", arg)),
+		}
+	}
+
+	let latitude = latitude.ok_or("missing --lat")?;
+	let longitude = longitude.ok_or("missing --lon")?;
+	let mut observer = Coordinates::new(latitude, longitude)
+		.and_then(|coords| coords.with_elevation(elevation))
+		.map(Observer::new)
+		.map_err(|error| error.to_string())?;
+	if let Some(temperature) = temperature {
+		observer = observer
+			.with_temperature(temperature)
+			.map_err(|error| error.to_string())?;
+	}
+	if let Some(pressure) = pressure {
+		observer = observer
+			.with_pressure(pressure)
+			.map_err(|error| error.to_string())?;
+	}
+
+	let span = match (date, month, from, to, next) {
+		(date, None, None, None, false) => Span::Day(date),
+		(None, Some(first), None, None, false) => {
+			let next_month = if first.month() == 12 {
+				NaiveDate::from_ymd(first.year() + 1, 1, 1)
+			} else {
+				NaiveDate::from_ymd(first.year(), first.month() + 1, 1)
+			};
+			Span::Range(first, next_month.pred())
+		}
+		(None, None, Some(from), Some(to), false) if from <= to => Span::Range(from, to),
+		(None, None, Some(_), Some(_), false) => return Err("--from is after --to".to_string()),
+		(None, None, Some(_), None, false) | (None, None, None, Some(_), false) => {
+			return Err("--from and --to go together".to_string())
+		}
+		(None, None, None, None, true) => Span::Next,
+		_ => return Err("--date, --month, --from/--to and --next are exclusive".to_string()),
+	};
+
+	Ok(Options {
+		observer,
+		span,
+		method,
+		asr,
+		midnight,
+		high_lats,
+		polar,
+		elevation: elevation_method,
+		twilight_elevation,
+		zone,
+		format,
+		clock,
+	})
+}
+
+fn parse_number(name: &str, value: &str) -> Result<f64, String> {
+	value
+		.parse()
+		.map_err(|_| format!("invalid number for {}: {}", name, value))
+}
+
+fn parse_name<T: FromStr<Err = Error>>(value: &str) -> Result<T, String> {
+	value.parse().map_err(|error: Error| error.to_string())
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+	NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| format!("invalid date: {}", value))
+}
+
+fn parse_zone(value: &str) -> Result<Zone, String> {
+	if value.eq_ignore_ascii_case("local") {
+		return Ok(Zone::Local);
+	}
+	if value.eq_ignore_ascii_case("utc") || value == "Z" {
+		return Ok(Zone::Fixed(FixedOffset::east(0)));
+	}
+
+	if let Some(sign) = value
+		.chars()
+		.next()
+		.filter(|sign| *sign == '+' || *sign == '-')
+	{
+		let (hours, minutes) = match value[1..].find(':') {
+			Some(index) => (&value[1..index + 1], &value[index + 2..]),
+			None => (&value[1..], "0"),
+		};
+		if let (Ok(hours), Ok(minutes)) = (hours.parse::<i32>(), minutes.parse::<i32>()) {
+			let seconds = (hours * 60 + minutes) * 60;
+			let seconds = if sign == '-' { -seconds } else { seconds };
+			if let Some(offset) = FixedOffset::east_opt(seconds) {
+				return Ok(Zone::Fixed(offset));
+			}
+		}
+		return Err(format!("invalid offset: {}", value));
+	}
+
+	#[cfg(feature = "chrono-tz")]
+	{
+		value
+			.parse()
+			.map(Zone::Iana)
+			.map_err(|_| format!("unknown time zone: {}", value))
+	}
+	#[cfg(not(feature = "chrono-tz"))]
+	Err(format!(
+		"unknown time zone: {} (IANA names need the chrono-tz feature)",
+		value
+	))
+}
+
+fn parse_format(value: &str) -> Result<Format, String> {
+	match value.to_lowercase().as_str() {
+		"text" => Ok(Format::Text),
+		"json" => Ok(Format::Json),
+		"csv" => Ok(Format::Csv),
+		_ => Err(format!("unknown format: {}", value)),
+	}
+}
+
+fn run<Tz: TimeZone>(options: &Options, tz: Tz) -> Result<(), String>
+where
+	Tz::Offset: fmt::Display,
+{
+	let mut method = PrayerManager::get_calculation_method(options.method);
+	if let Some(asr) = options.asr {
+		method = method.with_asr(asr);
+	}
+	if let Some(midnight) = options.midnight {
+		method = method.with_midnight(midnight);
+	}
+	let mut prayer_manager =
+		PrayerManager::new(CalculationMethods::Custom(method), options.high_lats)
+			.with_elevation(options.elevation)
+			.with_twilight_elevation(options.twilight_elevation);
+	if let Some(polar) = options.polar {
+		prayer_manager = prayer_manager.with_polar(polar);
+	}
+	let now = Utc::now().with_timezone(&tz);
+
+	let (start, end) = match options.span {
+		Span::Day(date) => {
+			let date = date.unwrap_or_else(|| now.date().naive_local());
+			(date, date)
+		}
+		Span::Range(start, end) => (start, end),
+		Span::Next => return print_next(options, &prayer_manager, now),
+	};
+	let local_date = |date: NaiveDate| {
+		tz.from_local_date(&date)
+			.earliest()
+			.ok_or_else(|| format!("invalid local date: {}", date))
+	};
+	let timetable = prayer_manager.timetable(local_date(start)?, local_date(end)?, options.observer);
+
+	let stdout = io::stdout();
+	let mut out = stdout.lock();
+	let result = match options.format {
+		Format::Text if start == end => print_day(&mut out, options.clock, timetable),
+		Format::Text => print_table(&mut out, options.clock, timetable),
+		Format::Json => JsonWriter::new().write(&mut out, timetable),
+		Format::Csv => CsvWriter::new()
+			.with_clock(options.clock)
+			.write(&mut out, timetable),
+	};
+	output(result)
+}
+
+fn format_time<Tz: TimeZone>(time: &Option<DateTime<Tz>>, clock: Clock) -> String
+where
+	Tz::Offset: fmt::Display,
+{
+	match time {
+		Some(time) => clock.format(time),
+		None => "--:--".to_string(),
+	}
+}
+
+fn print_day<W: Write, Tz: TimeZone>(
+	out: &mut W,
+	clock: Clock,
+	mut timetable: Timetable<'_, Tz>,
+) -> io::Result<()>
+where
+	Tz::Offset: fmt::Display,
+{
+	if let Some((date, times)) = timetable.next() {
+		writeln!(out, "{:<10}{}", "date", date.format("%Y-%m-%d (%:z)"))?;
+		for (prayer, time) in times.iter() {
+			writeln!(out, "{:<10}{}", prayer.name(), format_time(time, clock))?;
+		}
+	}
+	Ok(())
+}
+
+fn print_table<W: Write, Tz: TimeZone>(
+	out: &mut W,
+	clock: Clock,
+	timetable: Timetable<'_, Tz>,
+) -> io::Result<()>
+where
+	Tz::Offset: fmt::Display,
+{
+	let mut line = format!("{:<12}", "date");
+	for prayer in Prayer::ALL.iter() {
+		line += &format!("{:<10}", prayer.name());
+	}
+	writeln!(out, "{}", line.trim_end())?;
+
+	for (date, times) in timetable {
+		let mut line = format!("{:<12}", date.format("%Y-%m-%d").to_string());
+		for prayer in Prayer::ALL.iter() {
+			line += &format!("{:<10}", format_time(times.get(*prayer), clock));
+		}
+		writeln!(out, "{}", line.trim_end())?;
+	}
+	Ok(())
+}
+
+fn print_next<Tz: TimeZone>(
+	options: &Options,
+	prayer_manager: &PrayerManager,
+	now: DateTime<Tz>,
+) -> Result<(), String>
+where
+	Tz::Offset: fmt::Display,
+{
+	let current = prayer_manager.current_prayer(now.clone(), options.observer);
+	let next = prayer_manager
+		.next_prayer(now, options.observer)
+		.ok_or("no next prayer within a day")?;
+
+	let stdout = io::stdout();
+	let mut out = stdout.lock();
+	output(write_next(
+		&mut out,
+		options.format,
+		options.clock,
+		current,
+		next,
+	))
+}
+
+fn write_next<W: Write, Tz: TimeZone>(
+	out: &mut W,
+	format: Format,
+	clock: Clock,
+	current: Option<PrayerPeriod<Tz>>,
+	next: PrayerPeriod<Tz>,
+) -> io::Result<()>
+where
+	Tz::Offset: fmt::Display,
+{
+	let remaining = next.remaining.num_minutes();
+
+	match format {
+		Format::Text => {
+			if let Some(current) = &current {
+				writeln!(
+					out,
+					"{:<10}{:<10}{}",
+					"current",
+					current.prayer.name(),
+					format_time(&Some(current.start.clone()), clock)
+				)?;
+			}
+			writeln!(
+				out,
+				"{:<10}{:<10}{}  in {}:{:02}",
+				"next",
+				next.prayer.name(),
+				format_time(&Some(next.start.clone()), clock),
+				remaining / 60,
+				remaining % 60
+			)
+		}
+		Format::Json => {
+			let current = match &current {
+				Some(current) => format!(
+					"{{\"prayer\": \"{}\", \"start\": \"{}\"}}",
+					current.prayer.name(),
+					current.start.to_rfc3339_opts(SecondsFormat::Secs, false)
+				),
+				None => "null".to_string(),
+			};
+			writeln!(
+				out,
+				"{{\"current\": {}, \"next\": {{\"prayer\": \"{}\", \"start\": \"{}\", \"remaining\": {}}}}}",
+				current,
+				next.prayer.name(),
+				next.start.to_rfc3339_opts(SecondsFormat::Secs, false),
+				next.remaining.num_seconds()
+			)
+		}
+		Format::Csv => {
+			writeln!(
+				out,
+				"current,current_start,next,next_start,remaining_minutes"
+			)?;
+			let (name, start) = match &current {
+				Some(current) => (
+					current.prayer.name(),
+					format_time(&Some(current.start.clone()), clock),
+				),
+				None => ("", String::new()),
+			};
+			writeln!(
+				out,
+				"{},{},{},{},{}",
+				name,
+				start,
+				next.prayer.name(),
+				format_time(&Some(next.start.clone()), clock),
+				remaining
+			)
+		}
+	}
+}
+
+/// The result of writing to the standard output, a closed pipe (as with `head`) is not an error
+fn output(result: io::Result<()>) -> Result<(), String> {
+	match result {
+		Err(error) if error.kind() != io::ErrorKind::BrokenPipe => Err(error.to_string()),
+		_ => Ok(()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(line: &str) -> Result<Options, String> {
+		let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+		parse_args(&args)
+	}
+
+	#[test]
+	fn parse_arguments() {
+		let options = parse("--lat 51.5 --lon=-0.5").unwrap();
+		assert_eq!(options.observer.coords, Coordinates(51.5, -0.5, 0.0));
+		assert_eq!(options.span, Span::Day(None));
+		assert_eq!(options.method, CalculationMethods::MWL);
+		assert_eq!(options.polar, None);
+		assert_eq!(options.elevation, ElevationMethod::Dip);
+		assert_eq!(options.zone, Zone::Local);
+		assert_eq!(options.format, Format::Text);
+		assert_eq!(options.clock, Clock::H24);
+
+		let options = parse(
+			"--lat 69.75 --lon 18.5 --elevation 10 --method isna --asr hanafi \
+			 --high-lats AqrabAlBilad:48.5 --polar ReferenceLatitude:60 --horizon Ignore \
+			 --tz +01:00 --format csv --12h --date 2021-12-21",
+		)
+		.unwrap();
+		assert_eq!(options.observer.coords, Coordinates(69.75, 18.5, 10.0));
+		assert_eq!(options.method, CalculationMethods::ISNA);
+		assert_eq!(options.asr, Some(AsrJuristic::Hanafi));
+		assert_eq!(options.high_lats, Some(HightLatMethods::AqrabAlBilad(48.5)));
+		assert_eq!(options.polar, Some(PolarMethods::ReferenceLatitude(60.0)));
+		assert_eq!(options.elevation, ElevationMethod::Ignore);
+		assert_eq!(options.zone, Zone::Fixed(FixedOffset::east(3600)));
+		assert_eq!(options.format, Format::Csv);
+		assert_eq!(options.clock, Clock::H12);
+		assert_eq!(
+			options.span,
+			Span::Day(Some(NaiveDate::from_ymd(2021, 12, 21)))
+		);
+
+		assert!(parse("--lon 0").is_err());
+		assert!(parse("--lat 91 --lon 0").is_err());
+		assert!(parse("--lat 0 --lon 0 --polar midnight").is_err());
+		assert!(parse("--lat 0 --lon 0 --method").is_err());
+		assert!(parse("--lat 0 --lon 0 --verbose").is_err());
+	}
+
+	#[test]
+	fn parse_exclusive_spans() {
+		assert_eq!(
+			parse("--lat 0 --lon 0 --month 2021-02").unwrap().span,
+			Span::Range(
+				NaiveDate::from_ymd(2021, 2, 1),
+				NaiveDate::from_ymd(2021, 2, 28)
+			)
+		);
+		assert_eq!(
+			parse("--lat 0 --lon 0 --month 2021-12").unwrap().span,
+			Span::Range(
+				NaiveDate::from_ymd(2021, 12, 1),
+				NaiveDate::from_ymd(2021, 12, 31)
+			)
+		);
+		assert_eq!(
+			parse("--lat 0 --lon 0 --from 2021-04-01 --to 2021-04-01")
+				.unwrap()
+				.span,
+			Span::Range(
+				NaiveDate::from_ymd(2021, 4, 1),
+				NaiveDate::from_ymd(2021, 4, 1)
+			)
+		);
+		assert_eq!(parse("--lat 0 --lon 0 --next").unwrap().span, Span::Next);
+
+		assert!(parse("--lat 0 --lon 0 --from 2021-04-02 --to 2021-04-01").is_err());
+		assert!(parse("--lat 0 --lon 0 --from 2021-04-01").is_err());
+		assert!(parse("--lat 0 --lon 0 --to 2021-04-01").is_err());
+		assert!(parse("--lat 0 --lon 0 --date 2021-04-01 --month 2021-04").is_err());
+		assert!(parse("--lat 0 --lon 0 --date 2021-04-01 --next").is_err());
+		assert!(parse("--lat 0 --lon 0 --month 2021-04 --from 2021-04-01 --to 2021-04-02").is_err());
+	}
+
+	#[test]
+	fn parse_zones() {
+		assert_eq!(parse_zone("local"), Ok(Zone::Local));
+		assert_eq!(parse_zone("UTC"), Ok(Zone::Fixed(FixedOffset::east(0))));
+		assert_eq!(parse_zone("Z"), Ok(Zone::Fixed(FixedOffset::east(0))));
+		assert_eq!(
+			parse_zone("+03:00"),
+			Ok(Zone::Fixed(FixedOffset::east(3 * 3600)))
+		);
+		assert_eq!(
+			parse_zone("-04:30"),
+			Ok(Zone::Fixed(FixedOffset::west(4 * 3600 + 30 * 60)))
+		);
+		assert_eq!(
+			parse_zone("+5"),
+			Ok(Zone::Fixed(FixedOffset::east(5 * 3600)))
+		);
+		assert!(parse_zone("+25:00").is_err());
+		assert!(parse_zone("+ab").is_err());
+		#[cfg(feature = "chrono-tz")]
+		assert_eq!(
+			parse_zone("Europe/Paris"),
+			Ok(Zone::Iana(chrono_tz::Europe::Paris))
+		);
+		#[cfg(not(feature = "chrono-tz"))]
+		assert!(parse_zone("Europe/Paris").is_err());
+	}
+
+	#[test]
+	fn format_times() {
+		let a_time = Utc.ymd(2021, 4, 12).and_hms(5, 4, 30);
+		assert_eq!(format_time(&Some(a_time), Clock::H24), "05:05");
+		assert_eq!(format_time(&Some(a_time), Clock::H12), "5:05 AM");
+		assert_eq!(format_time::<Utc>(&None, Clock::H24), "--:--");
+	}
+}
@@ -0,0 +1,96 @@
+use chrono::{Date, Datelike, NaiveDate, Utc};
+
+/// The twilight used by the Moonsighting Committee Worldwide for isha
+///
+/// https://www.moonsighting.com/isha_fajr.html
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shafaq {
+	/// A combination of ahmer and abyad, to reduce difficulties at higher latitudes
+	General,
+	/// Red twilight, the earliest isha
+	Ahmer,
+	/// White twilight, the latest isha
+	Abyad,
+}
+
+/// Minutes between fajr and sunrise
+pub fn morning_twilight(latitude: f64, date: &Date<Utc>) -> f64 {
+	let days = days_since_solstice(latitude, date);
+	let latitude = latitude.abs();
+	seasonal_minutes(
+		[
+			75.0 + 28.65 / 55.0 * latitude,
+			75.0 + 19.44 / 55.0 * latitude,
+			75.0 + 32.74 / 55.0 * latitude,
+			75.0 + 48.10 / 55.0 * latitude,
+		],
+		days,
+	)
+}
+
+/// Minutes between sunset and isha
+pub fn evening_twilight(latitude: f64, date: &Date<Utc>, shafaq: Shafaq) -> f64 {
+	let days = days_since_solstice(latitude, date);
+	let latitude = latitude.abs();
+	let curve = match shafaq {
+		Shafaq::General => [
+			75.0 + 25.60 / 55.0 * latitude,
+			75.0 + 2.050 / 55.0 * latitude,
+			75.0 - 9.210 / 55.0 * latitude,
+			75.0 + 6.140 / 55.0 * latitude,
+		],
+		Shafaq::Ahmer => [
+			62.0 + 17.40 / 55.0 * latitude,
+			62.0 - 7.160 / 55.0 * latitude,
+			62.0 + 5.120 / 55.0 * latitude,
+			62.0 + 19.44 / 55.0 * latitude,
+		],
+		Shafaq::Abyad => [
+			75.0 + 25.60 / 55.0 * latitude,
+			75.0 + 7.160 / 55.0 * latitude,
+			75.0 + 36.84 / 55.0 * latitude,
+			75.0 + 81.84 / 55.0 * latitude,
+		],
+	};
+
+	seasonal_minutes(curve, days)
+}
+
+/// Interpolate between the values of the curve at the winter solstice (a), 91 days later (b),
+/// 137 days later (c) and at the summer solstice (d), and back
+fn seasonal_minutes([a, b, c, d]: [f64; 4], days: f64) -> f64 {
+	if days < 91.0 {
+		a + (b - a) / 91.0 * days
+	} else if days < 137.0 {
+		b + (c - b) / 46.0 * (days - 91.0)
+	} else if days < 183.0 {
+		c + (d - c) / 46.0 * (days - 137.0)
+	} else if days < 229.0 {
+		d + (c - d) / 46.0 * (days - 183.0)
+	} else if days < 275.0 {
+		c + (b - c) / 46.0 * (days - 229.0)
+	} else {
+		b + (a - b) / 91.0 * (days - 275.0)
+	}
+}
+
+/// Days since the winter solstice of the hemisphere
+fn days_since_solstice(latitude: f64, date: &Date<Utc>) -> f64 {
+	let days_in_year = NaiveDate::from_ymd(date.year(), 12, 31).ordinal() as f64;
+	let day = date.ordinal() as f64;
+
+	let days = if latitude >= 0.0 {
+		day + 10.0
+	} else {
+		day - (days_in_year - 193.0)
+	};
+
+	if days >= days_in_year {
+		days - days_in_year
+	} else if days < 0.0 {
+		days + days_in_year
+	} else {
+		days
+	}
+}
@@ -0,0 +1,505 @@
+use crate::moonsighting::Shafaq;
+use crate::prayer::{
+	AsrJuristic, CalculationMethod, CalculationMethods, CalculationType, ElevationMethod,
+	HightLatMethods, MidnightMethod, PolarMethods, Prayer, Tune,
+};
+use crate::Error;
+use std::fmt;
+use std::str::FromStr;
+
+impl CalculationMethods {
+	/// The full name of the method
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// assert_eq!(CalculationMethods::ISNA.full_name(), "Islamic Society of North America");
+	/// ~~~~
+	pub fn full_name(&self) -> &'static str {
+		match self {
+			CalculationMethods::MWL => "Muslim World League",
+			CalculationMethods::ISNA => "Islamic Society of North America",
+			CalculationMethods::Egypt => "Egyptian General Authority of Survey",
+			CalculationMethods::Makkah(false) => "Umm Al-Qura University, Makkah",
+			CalculationMethods::Makkah(true) => "Umm Al-Qura University, Makkah (Ramadan)",
+			CalculationMethods::Karachi => "University of Islamic Sciences, Karachi",
+			CalculationMethods::Tehran => "Institute of Geophysics, University of Tehran",
+			CalculationMethods::Jafari => "Shia Ithna-Ashari, Leva Institute, Qum",
+			CalculationMethods::MF => "Muslims of France",
+			CalculationMethods::Gulf => "Gulf Region",
+			CalculationMethods::Kuwait => "Kuwait",
+			CalculationMethods::Qatar => "Qatar",
+			CalculationMethods::Singapore => "Majlis Ugama Islam Singapura",
+			CalculationMethods::Malaysia => "Jabatan Kemajuan Islam Malaysia",
+			CalculationMethods::Indonesia => "Kementerian Agama Republik Indonesia",
+			CalculationMethods::Turkey => "Diyanet İşleri Başkanlığı",
+			CalculationMethods::Dubai => "Dubai",
+			CalculationMethods::Russia => "Spiritual Administration of Muslims of Russia",
+			CalculationMethods::Algeria => "Ministry of Religious Affairs and Wakfs, Algeria",
+			CalculationMethods::Tunisia => "Ministry of Religious Affairs, Tunisia",
+			CalculationMethods::Morocco => "Ministry of Habous and Islamic Affairs, Morocco",
+			CalculationMethods::Jordan => "Ministry of Awqaf, Islamic Affairs and Holy Places, Jordan",
+			CalculationMethods::Portugal => "Comunidade Islâmica de Lisboa",
+			CalculationMethods::MoonsightingCommittee(_) => "Moonsighting Committee Worldwide",
+			CalculationMethods::Custom(_) => "Custom",
+		}
+	}
+
+	/// The region where the method is commonly used (`None` for custom parameters)
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// assert_eq!(CalculationMethods::Karachi.region(), Some("Pakistan, Afghanistan, Bangladesh, India"));
+	/// ~~~~
+	pub fn region(&self) -> Option<&'static str> {
+		Some(match self {
+			CalculationMethods::MWL => "Europe, Far East, parts of the US",
+			CalculationMethods::ISNA => "North America",
+			CalculationMethods::Egypt => "Africa, Syria, Lebanon, Malaysia",
+			CalculationMethods::Makkah(_) => "Arabian Peninsula",
+			CalculationMethods::Karachi => "Pakistan, Afghanistan, Bangladesh, India",
+			CalculationMethods::Tehran => "Iran, some regions of Afghanistan",
+			CalculationMethods::Jafari => "Some Shia communities worldwide",
+			CalculationMethods::MF => "France",
+			CalculationMethods::Gulf => "Gulf states",
+			CalculationMethods::Kuwait => "Kuwait",
+			CalculationMethods::Qatar => "Qatar",
+			CalculationMethods::Singapore => "Singapore",
+			CalculationMethods::Malaysia => "Malaysia",
+			CalculationMethods::Indonesia => "Indonesia",
+			CalculationMethods::Turkey => "Turkey",
+			CalculationMethods::Dubai => "United Arab Emirates",
+			CalculationMethods::Russia => "Russia",
+			CalculationMethods::Algeria => "Algeria",
+			CalculationMethods::Tunisia => "Tunisia",
+			CalculationMethods::Morocco => "Morocco",
+			CalculationMethods::Jordan => "Jordan",
+			CalculationMethods::Portugal => "Portugal",
+			CalculationMethods::MoonsightingCommittee(_) => "North America, United Kingdom",
+			CalculationMethods::Custom(_) => return None,
+		})
+	}
+}
+
+/// The canonical identifier, or the compact form of custom parameters (which `FromStr` parses)
+impl fmt::Display for CalculationMethods {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			CalculationMethods::MWL => "MWL",
+			CalculationMethods::ISNA => "ISNA",
+			CalculationMethods::Egypt => "Egypt",
+			CalculationMethods::Makkah(false) => "Makkah",
+			CalculationMethods::Makkah(true) => "Makkah-Ramadan",
+			CalculationMethods::Karachi => "Karachi",
+			CalculationMethods::Tehran => "Tehran",
+			CalculationMethods::Jafari => "Jafari",
+			CalculationMethods::MF => "MF",
+			CalculationMethods::Gulf => "Gulf",
+			CalculationMethods::Kuwait => "Kuwait",
+			CalculationMethods::Qatar => "Qatar",
+			CalculationMethods::Singapore => "Singapore",
+			CalculationMethods::Malaysia => "Malaysia",
+			CalculationMethods::Indonesia => "Indonesia",
+			CalculationMethods::Turkey => "Turkey",
+			CalculationMethods::Dubai => "Dubai",
+			CalculationMethods::Russia => "Russia",
+			CalculationMethods::Algeria => "Algeria",
+			CalculationMethods::Tunisia => "Tunisia",
+			CalculationMethods::Morocco => "Morocco",
+			CalculationMethods::Jordan => "Jordan",
+			CalculationMethods::Portugal => "Portugal",
+			CalculationMethods::MoonsightingCommittee(Shafaq::General) => "Moonsighting",
+			CalculationMethods::MoonsightingCommittee(Shafaq::Ahmer) => "Moonsighting-Ahmer",
+			CalculationMethods::MoonsightingCommittee(Shafaq::Abyad) => "Moonsighting-Abyad",
+			CalculationMethods::Custom(method) => return write_custom(f, method),
+		};
+		f.write_str(name)
+	}
+}
+
+/// Parse a canonical identifier or an alias (case, `-`, `_` and spaces are ignored),
+/// or custom parameters in a compact form
+///
+/// The compact form is a comma-separated list of `parameter=value`: `fajr` (an angle), `isha`,
+/// `imsak` and `maghrib` (an angle, or minutes with a `min` suffix), `dhuhr` (minutes), `asr`,
+/// `midnight`, `high-lats`, `shafaq` and `tune.<prayer>` (minutes added to the time of a
+/// [prayer](Prayer::name)). Fajr and isha are required.
+/// Angles may have a `deg` suffix, and minutes a `min` suffix.
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// assert_eq!("MWL".parse(), Ok(CalculationMethods::MWL));
+/// assert_eq!("umm-al-qura".parse(), Ok(CalculationMethods::Makkah(false)));
+/// assert_eq!("Makkah-Ramadan".parse(), Ok(CalculationMethods::Makkah(true)));
+///
+/// let method: CalculationMethods = "fajr=18,isha=90min,maghrib=4deg".parse()?;
+/// assert_eq!(
+///     method,
+///     CalculationMethods::Custom(CalculationMethod::new(
+///         None,
+///         18.0,
+///         None,
+///         Some(CalculationType::Angle(4.0)),
+///         CalculationType::Minutes(90.0),
+///         None,
+///     ))
+/// );
+/// # Ok::<(), Error>(())
+/// ~~~~
+impl FromStr for CalculationMethods {
+	type Err = Error;
+
+	fn from_str(value: &str) -> Result<CalculationMethods, Error> {
+		if value.contains('=') {
+			return parse_custom(value).map(CalculationMethods::Custom);
+		}
+
+		Ok(match normalize(value).as_str() {
+			"mwl" | "muslimworldleague" => CalculationMethods::MWL,
+			"isna" | "northamerica" => CalculationMethods::ISNA,
+			"egypt" | "egyptian" => CalculationMethods::Egypt,
+			"makkah" | "mecca" | "ummalqura" => CalculationMethods::Makkah(false),
+			"makkahramadan" | "meccaramadan" | "ummalquraramadan" => CalculationMethods::Makkah(true),
+			"karachi" => CalculationMethods::Karachi,
+			"tehran" => CalculationMethods::Tehran,
+			"jafari" | "shia" | "qum" => CalculationMethods::Jafari,
+			"mf" | "france" | "musulmansdefrance" | "uoif" => CalculationMethods::MF,
+			"gulf" => CalculationMethods::Gulf,
+			"kuwait" => CalculationMethods::Kuwait,
+			"qatar" => CalculationMethods::Qatar,
+			"singapore" | "muis" => CalculationMethods::Singapore,
+			"malaysia" | "jakim" => CalculationMethods::Malaysia,
+			"indonesia" | "kemenag" => CalculationMethods::Indonesia,
+			"turkey" | "diyanet" => CalculationMethods::Turkey,
+			"dubai" | "uae" => CalculationMethods::Dubai,
+			"russia" => CalculationMethods::Russia,
+			"algeria" => CalculationMethods::Algeria,
+			"tunisia" => CalculationMethods::Tunisia,
+			"morocco" => CalculationMethods::Morocco,
+			"jordan" => CalculationMethods::Jordan,
+			"portugal" | "lisbon" => CalculationMethods::Portugal,
+			"moonsighting" | "moonsightingcommittee" | "moonsightinggeneral" => {
+				CalculationMethods::MoonsightingCommittee(Shafaq::General)
+			}
+			"moonsightingahmer" => CalculationMethods::MoonsightingCommittee(Shafaq::Ahmer),
+			"moonsightingabyad" => CalculationMethods::MoonsightingCommittee(Shafaq::Abyad),
+			_ => return Err(Error::UnknownName("calculation method", value.to_string())),
+		})
+	}
+}
+
+impl fmt::Display for AsrJuristic {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			AsrJuristic::Standard => "Standard",
+			AsrJuristic::Hanafi => "Hanafi",
+		})
+	}
+}
+
+/// Parse `Standard` (or `Shafii`) or `Hanafi`, ignoring case
+impl FromStr for AsrJuristic {
+	type Err = Error;
+
+	fn from_str(value: &str) -> Result<AsrJuristic, Error> {
+		match normalize(value).as_str() {
+			"standard" | "shafii" => Ok(AsrJuristic::Standard),
+			"hanafi" => Ok(AsrJuristic::Hanafi),
+			_ => Err(Error::UnknownName("asr juristic", value.to_string())),
+		}
+	}
+}
+
+impl fmt::Display for MidnightMethod {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			MidnightMethod::Standard => "Standard",
+			MidnightMethod::Jafari => "Jafari",
+		})
+	}
+}
+
+/// Parse `Standard` or `Jafari`, ignoring case
+impl FromStr for MidnightMethod {
+	type Err = Error;
+
+	fn from_str(value: &str) -> Result<MidnightMethod, Error> {
+		match normalize(value).as_str() {
+			"standard" => Ok(MidnightMethod::Standard),
+			"jafari" => Ok(MidnightMethod::Jafari),
+			_ => Err(Error::UnknownName("midnight method", value.to_string())),
+		}
+	}
+}
+
+/// The name of the method, followed by `:` and the latitude for methods which take one
+impl fmt::Display for HightLatMethods {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			HightLatMethods::NightMiddle => f.write_str("NightMiddle"),
+			HightLatMethods::AngleBased => f.write_str("AngleBased"),
+			HightLatMethods::OneSeventh => f.write_str("OneSeventh"),
+			HightLatMethods::NearestLatitude(latitude) => write!(f, "NearestLatitude:{}", latitude),
+			HightLatMethods::NearestGoodDay => f.write_str("NearestGoodDay"),
+			HightLatMethods::AqrabAlBilad(latitude) => write!(f, "AqrabAlBilad:{}", latitude),
+		}
+	}
+}
+
+/// Parse the name of a method, ignoring case, `-`, `_` and spaces
+///
+/// The latitude of `NearestLatitude` and `AqrabAlBilad` follows a `:`,
+/// and defaults to 48.5° and 45° respectively.
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// assert_eq!("night-middle".parse(), Ok(HightLatMethods::NightMiddle));
+/// assert_eq!("AqrabAlBilad:48.5".parse(), Ok(HightLatMethods::AqrabAlBilad(48.5)));
+/// ~~~~
+impl FromStr for HightLatMethods {
+	type Err = Error;
+
+	fn from_str(value: &str) -> Result<HightLatMethods, Error> {
+		let (name, latitude) = match value.find(':') {
+			Some(index) => (
+				&value[..index],
+				Some(parse_number("latitude", &value[index + 1..])?),
+			),
+			None => (value, None),
+		};
+
+		match (normalize(name).as_str(), latitude) {
+			("nightmiddle" | "middleofthenight", None) => Ok(HightLatMethods::NightMiddle),
+			("anglebased" | "twilightangle", None) => Ok(HightLatMethods::AngleBased),
+			("oneseventh" | "seventhofthenight", None) => Ok(HightLatMethods::OneSeventh),
+			("nearestlatitude", latitude) => {
+				Ok(HightLatMethods::NearestLatitude(latitude.unwrap_or(48.5)))
+			}
+			("nearestgoodday" | "nearestday", None) => Ok(HightLatMethods::NearestGoodDay),
+			("aqrabalbilad" | "nearestcity", latitude) => {
+				Ok(HightLatMethods::AqrabAlBilad(latitude.unwrap_or(45.0)))
+			}
+			_ => Err(Error::UnknownName(
+				"high latitudes method",
+				value.to_string(),
+			)),
+		}
+	}
+}
+
+/// The name of the method, followed by `:` and the latitude for `ReferenceLatitude`
+impl fmt::Display for PolarMethods {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			PolarMethods::NearestDay => f.write_str("NearestDay"),
+			PolarMethods::ReferenceLatitude(latitude) => write!(f, "ReferenceLatitude:{}", latitude),
+		}
+	}
+}
+
+/// Parse `NearestDay` or `ReferenceLatitude[:<degrees>]` (65° by default), ignoring case,
+/// `-`, `_` and spaces
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// assert_eq!("nearest-day".parse(), Ok(PolarMethods::NearestDay));
+/// assert_eq!("ReferenceLatitude:60".parse(), Ok(PolarMethods::ReferenceLatitude(60.0)));
+/// ~~~~
+impl FromStr for PolarMethods {
+	type Err = Error;
+
+	fn from_str(value: &str) -> Result<PolarMethods, Error> {
+		let (name, latitude) = match value.find(':') {
+			Some(index) => (
+				&value[..index],
+				Some(parse_number("latitude", &value[index + 1..])?),
+			),
+			None => (value, None),
+		};
+
+		match (normalize(name).as_str(), latitude) {
+			("nearestday", None) => Ok(PolarMethods::NearestDay),
+			("referencelatitude", latitude) => {
+				Ok(PolarMethods::ReferenceLatitude(latitude.unwrap_or(65.0)))
+			}
+			_ => Err(Error::UnknownName("polar method", value.to_string())),
+		}
+	}
+}
+
+/// The name of the method, followed by `:` and the elevation of the horizon for `Horizon`
+impl fmt::Display for ElevationMethod {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ElevationMethod::Ignore => f.write_str("Ignore"),
+			ElevationMethod::Dip => f.write_str("Dip"),
+			ElevationMethod::Horizon(elevation) => write!(f, "Horizon:{}", elevation),
+		}
+	}
+}
+
+/// Parse `Ignore` (or `SeaLevel`), `Dip` or `Horizon:<meters>`, ignoring case
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// assert_eq!("sea-level".parse(), Ok(ElevationMethod::Ignore));
+/// assert_eq!("Horizon:1100".parse(), Ok(ElevationMethod::Horizon(1100.0)));
+/// ~~~~
+impl FromStr for ElevationMethod {
+	type Err = Error;
+
+	fn from_str(value: &str) -> Result<ElevationMethod, Error> {
+		let (name, elevation) = match value.find(':') {
+			Some(index) => (
+				&value[..index],
+				Some(parse_number("horizon elevation", &value[index + 1..])?),
+			),
+			None => (value, None),
+		};
+
+		match (normalize(name).as_str(), elevation) {
+			("ignore" | "sealevel", None) => Ok(ElevationMethod::Ignore),
+			("dip", None) => Ok(ElevationMethod::Dip),
+			("horizon", Some(elevation)) => Ok(ElevationMethod::Horizon(elevation)),
+			("horizon", None) => Err(Error::MissingParameter("horizon elevation")),
+			_ => Err(Error::UnknownName("elevation method", value.to_string())),
+		}
+	}
+}
+
+/// Lowercase, without `-`, `_` and spaces
+fn normalize(name: &str) -> String {
+	name
+		.trim()
+		.chars()
+		.filter(|c| !matches!(c, '-' | '_' | ' '))
+		.flat_map(char::to_lowercase)
+		.collect()
+}
+
+fn parse_number(parameter: &'static str, value: &str) -> Result<f64, Error> {
+	value
+		.trim()
+		.parse()
+		.map_err(|_| Error::InvalidValue(parameter, value.to_string()))
+}
+
+/// An angle (optionally with a `deg` suffix) or minutes (with a `min` suffix)
+fn parse_type(parameter: &'static str, value: &str) -> Result<CalculationType, Error> {
+	let value = value.trim().to_lowercase();
+	if let Some(minutes) = value.strip_suffix("min") {
+		return Ok(CalculationType::Minutes(parse_number(parameter, minutes)?));
+	}
+	let angle = value.strip_suffix("deg").unwrap_or(&value);
+	Ok(CalculationType::Angle(parse_number(parameter, angle)?))
+}
+
+fn parse_custom(value: &str) -> Result<CalculationMethod, Error> {
+	let mut builder = CalculationMethod::builder();
+	let mut tune = Tune::default();
+
+	for parameter in value
+		.split(',')
+		.filter(|parameter| !parameter.trim().is_empty())
+	{
+		let (key, value) = match parameter.find('=') {
+			Some(index) => (&parameter[..index], &parameter[index + 1..]),
+			None => return Err(Error::InvalidValue("parameter", parameter.to_string())),
+		};
+
+		builder = match normalize(key).as_str() {
+			"imsak" => builder.imsak(parse_type("imsak", value)?),
+			"fajr" => match parse_type("fajr", value)? {
+				CalculationType::Angle(angle) => builder.fajr(angle),
+				CalculationType::Minutes(_) => return Err(Error::InvalidValue("fajr", value.to_string())),
+			},
+			"dhuhr" => builder.dhuhr(parse_minutes("dhuhr", value)?),
+			"asr" => builder.asr(value.parse()?),
+			"maghrib" => builder.maghrib(parse_type("maghrib", value)?),
+			"isha" => builder.isha(parse_type("isha", value)?),
+			"midnight" => builder.midnight(value.parse()?),
+			"highlats" => builder.high_lats(value.parse()?),
+			"shafaq" => builder.moonsighting(match normalize(value).as_str() {
+				"general" => Shafaq::General,
+				"ahmer" => Shafaq::Ahmer,
+				"abyad" => Shafaq::Abyad,
+				_ => return Err(Error::UnknownName("shafaq", value.to_string())),
+			}),
+			key if key.starts_with("tune.") => {
+				let minutes = IntoIterator::into_iter(tune_minutes(&mut tune))
+					.find(|(prayer, _)| prayer.name() == &key[5..])
+					.map(|(_, minutes)| minutes)
+					.ok_or_else(|| Error::UnknownName("prayer", key[5..].to_string()))?;
+				*minutes = parse_minutes("tune", value)?;
+				builder
+			}
+			_ => return Err(Error::UnknownName("parameter", key.to_string())),
+		};
+	}
+
+	builder.tune(tune).build()
+}
+
+/// Minutes, optionally with a `min` suffix
+fn parse_minutes(parameter: &'static str, value: &str) -> Result<f64, Error> {
+	let minutes = value.trim().to_lowercase();
+	parse_number(parameter, minutes.strip_suffix("min").unwrap_or(&minutes))
+}
+
+/// The minutes of a tune, by prayer
+fn tune_minutes(tune: &mut Tune) -> [(Prayer, &mut f64); 9] {
+	[
+		(Prayer::Imsak, &mut tune.imsak),
+		(Prayer::Fajr, &mut tune.fajr),
+		(Prayer::Sunrise, &mut tune.sunrise),
+		(Prayer::Dhuhr, &mut tune.dhuhr),
+		(Prayer::Asr, &mut tune.asr),
+		(Prayer::Sunset, &mut tune.sunset),
+		(Prayer::Maghrib, &mut tune.maghrib),
+		(Prayer::Isha, &mut tune.isha),
+		(Prayer::Midnight, &mut tune.midnight),
+	]
+}
+
+/// The compact form of custom parameters
+fn write_custom(f: &mut fmt::Formatter<'_>, method: &CalculationMethod) -> fmt::Result {
+	let format_type = |value: CalculationType| match value {
+		CalculationType::Angle(angle) => angle.to_string(),
+		CalculationType::Minutes(minutes) => format!("{}min", minutes),
+	};
+
+	write!(
+		f,
+		"imsak={},fajr={},dhuhr={}min,asr={},maghrib={},isha={},midnight={}",
+		format_type(method.imsak()),
+		method.fajr(),
+		method.dhuhr(),
+		method.asr(),
+		format_type(method.maghrib()),
+		format_type(method.isha()),
+		method.midnight()
+	)?;
+	if let Some(high_lats) = method.high_lats() {
+		write!(f, ",high-lats={}", high_lats)?;
+	}
+	if let Some(shafaq) = method.moonsighting() {
+		write!(f, ",shafaq={:?}", shafaq)?;
+	}
+	for (prayer, minutes) in tune_minutes(&mut method.tune()).iter() {
+		if **minutes != 0.0 {
+			write!(f, ",tune.{}={}min", prayer.name(), minutes)?;
+		}
+	}
+	Ok(())
+}
@@ -1,9 +1,14 @@
 use crate::astronomy::*;
 use crate::dmath;
-use chrono::{Date, Utc};
+use crate::ephemeris::{Ephemeris, Usno};
+use crate::moonsighting::{self, Shafaq};
+use crate::Error;
+use chrono::{Date, DateTime, Duration, NaiveDate, TimeZone, Utc};
+use std::cmp::Ordering;
 
 /// A calculation type
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CalculationType {
 	/// A degree value
 	Angle(f64),
@@ -20,6 +25,7 @@ impl CalculationType {
 
 /// The midnight method
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MidnightMethod {
 	/// from Sunset to Sunrise
 	Standard,
@@ -29,6 +35,7 @@ pub enum MidnightMethod {
 
 /// The asr juristic methods
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AsrJuristic {
 	/// factor: 1
 	Standard,
@@ -36,8 +43,44 @@ pub enum AsrJuristic {
 	Hanafi,
 }
 
+/// Minutes added to each computed time (may be negative)
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let tune = Tune {
+///     dhuhr: 2.0,
+///     maghrib: 3.0,
+///     ..Tune::default()
+/// };
+/// ~~~~
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tune {
+	/// Imsak
+	pub imsak: f64,
+	/// Fajr
+	pub fajr: f64,
+	/// Sunrise
+	pub sunrise: f64,
+	/// Dhur
+	pub dhuhr: f64,
+	/// Asr
+	pub asr: f64,
+	/// Sunset
+	pub sunset: f64,
+	/// Maghrif
+	pub maghrib: f64,
+	/// Isha
+	pub isha: f64,
+	/// Middle of the night
+	pub midnight: f64,
+}
+
 /// Represents a calculation method (parameters)
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CalculationMethod {
 	imsak: CalculationType,
 	/// angle
@@ -48,6 +91,9 @@ pub struct CalculationMethod {
 	maghrib: CalculationType,
 	isha: CalculationType,
 	midnight: MidnightMethod,
+	high_lats: Option<HightLatMethods>,
+	moonsighting: Option<Shafaq>,
+	tune: Tune,
 }
 
 impl CalculationMethod {
@@ -68,23 +114,283 @@ impl CalculationMethod {
 			maghrib: maghrib.unwrap_or(CalculationType::Minutes(0.0)),
 			isha,
 			midnight: midnight.unwrap_or(MidnightMethod::Standard),
+			high_lats: None,
+			moonsighting: None,
+			tune: Tune::default(),
 		}
 	}
 
+	/// Create a [`CalculationMethodBuilder`](CalculationMethodBuilder)
+	pub fn builder() -> CalculationMethodBuilder {
+		CalculationMethodBuilder::default()
+	}
+
 	/// Create a CalculationMethod from fajr (angle, degree) and isha
 	pub fn from(fajr: f64, isha: CalculationType) -> CalculationMethod {
 		CalculationMethod::new(None, fajr, None, None, isha, None)
 	}
+
+	/// Set the minutes added to each computed time
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// CalculationMethod::from(18.0, CalculationType::Angle(17.0)).with_tune(Tune {
+	///     dhuhr: 2.0,
+	///     ..Tune::default()
+	/// });
+	/// ~~~~
+	pub fn with_tune(mut self, tune: Tune) -> CalculationMethod {
+		self.tune = tune;
+		self
+	}
+
+	/// Set the asr juristic method
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let method = PrayerManager::get_calculation_method(CalculationMethods::MWL).with_asr(AsrJuristic::Hanafi);
+	/// assert_eq!(method.asr(), AsrJuristic::Hanafi);
+	/// ~~~~
+	pub fn with_asr(mut self, asr: AsrJuristic) -> CalculationMethod {
+		self.asr = asr;
+		self
+	}
+
+	/// Set the midnight method
+	pub fn with_midnight(mut self, midnight: MidnightMethod) -> CalculationMethod {
+		self.midnight = midnight;
+		self
+	}
+
+	/// Imsak
+	pub fn imsak(&self) -> CalculationType {
+		self.imsak
+	}
+
+	/// Fajr angle, in degrees
+	pub fn fajr(&self) -> f64 {
+		self.fajr
+	}
+
+	/// Minutes after mid-day for dhuhr
+	pub fn dhuhr(&self) -> f64 {
+		self.dhuhr
+	}
+
+	/// Asr juristic method
+	pub fn asr(&self) -> AsrJuristic {
+		self.asr
+	}
+
+	/// Maghrib
+	pub fn maghrib(&self) -> CalculationType {
+		self.maghrib
+	}
+
+	/// Isha
+	pub fn isha(&self) -> CalculationType {
+		self.isha
+	}
+
+	/// Midnight method
+	pub fn midnight(&self) -> MidnightMethod {
+		self.midnight
+	}
+
+	/// Default method for higher latitudes
+	pub fn high_lats(&self) -> Option<HightLatMethods> {
+		self.high_lats
+	}
+
+	/// Moonsighting Committee seasonal twilight
+	pub fn moonsighting(&self) -> Option<Shafaq> {
+		self.moonsighting
+	}
+
+	/// Minutes added to each computed time
+	pub fn tune(&self) -> Tune {
+		self.tune
+	}
+}
+
+/// Builds a validated [`CalculationMethod`](CalculationMethod)
+///
+/// Fajr and isha are required, other parameters default to those of
+/// [`CalculationMethod::new`](CalculationMethod::new).
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let method = CalculationMethod::builder()
+///   .fajr(18.0)
+///   .maghrib(CalculationType::Minutes(3.0))
+///   .isha(CalculationType::Minutes(90.0))
+///   .asr(AsrJuristic::Hanafi)
+///   .high_lats(HightLatMethods::AngleBased)
+///   .build()?;
+/// # Ok::<(), Error>(())
+/// ~~~~
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+pub struct CalculationMethodBuilder {
+	imsak: Option<CalculationType>,
+	fajr: Option<f64>,
+	dhuhr: Option<f64>,
+	asr: Option<AsrJuristic>,
+	maghrib: Option<CalculationType>,
+	isha: Option<CalculationType>,
+	midnight: Option<MidnightMethod>,
+	high_lats: Option<HightLatMethods>,
+	moonsighting: Option<Shafaq>,
+	tune: Option<Tune>,
+}
+
+impl CalculationMethodBuilder {
+	/// Imsak, an angle or minutes before fajr (default: 10 minutes)
+	pub fn imsak(mut self, imsak: CalculationType) -> CalculationMethodBuilder {
+		self.imsak = Some(imsak);
+		self
+	}
+
+	/// Fajr angle, in degrees (required)
+	pub fn fajr(mut self, angle: f64) -> CalculationMethodBuilder {
+		self.fajr = Some(angle);
+		self
+	}
+
+	/// Minutes after mid-day for dhuhr (default: 0)
+	pub fn dhuhr(mut self, minutes: f64) -> CalculationMethodBuilder {
+		self.dhuhr = Some(minutes);
+		self
+	}
+
+	/// Asr juristic method (default: standard)
+	pub fn asr(mut self, asr: AsrJuristic) -> CalculationMethodBuilder {
+		self.asr = Some(asr);
+		self
+	}
+
+	/// Maghrib, an angle or minutes after sunset (default: 0 minutes)
+	pub fn maghrib(mut self, maghrib: CalculationType) -> CalculationMethodBuilder {
+		self.maghrib = Some(maghrib);
+		self
+	}
+
+	/// Isha, an angle or minutes after maghrib (required)
+	pub fn isha(mut self, isha: CalculationType) -> CalculationMethodBuilder {
+		self.isha = Some(isha);
+		self
+	}
+
+	/// Midnight method (default: standard)
+	pub fn midnight(mut self, midnight: MidnightMethod) -> CalculationMethodBuilder {
+		self.midnight = Some(midnight);
+		self
+	}
+
+	/// Default method for higher latitudes, used when the [`PrayerManager`](PrayerManager) has none
+	pub fn high_lats(mut self, high_lats: HightLatMethods) -> CalculationMethodBuilder {
+		self.high_lats = Some(high_lats);
+		self
+	}
+
+	/// Bound fajr and isha by the seasonal twilight of the Moonsighting Committee (default: none)
+	///
+	/// See [`CalculationMethods::MoonsightingCommittee`](CalculationMethods::MoonsightingCommittee).
+	pub fn moonsighting(mut self, shafaq: Shafaq) -> CalculationMethodBuilder {
+		self.moonsighting = Some(shafaq);
+		self
+	}
+
+	/// Minutes added to each computed time (default: none)
+	pub fn tune(mut self, tune: Tune) -> CalculationMethodBuilder {
+		self.tune = Some(tune);
+		self
+	}
+
+	/// Validate the parameters and build the [`CalculationMethod`](CalculationMethod)
+	pub fn build(self) -> Result<CalculationMethod, Error> {
+		let fajr = self.fajr.ok_or(Error::MissingParameter("fajr"))?;
+		let isha = self.isha.ok_or(Error::MissingParameter("isha"))?;
+
+		let mut method = CalculationMethod::new(
+			self.imsak,
+			validate_angle("fajr", fajr)?,
+			self.asr,
+			self.maghrib,
+			isha,
+			self.midnight,
+		);
+		validate_type("imsak", method.imsak)?;
+		validate_type("maghrib", method.maghrib)?;
+		validate_type("isha", method.isha)?;
+
+		method.dhuhr = validate_minutes("dhuhr", self.dhuhr.unwrap_or(0.0))?;
+		method.high_lats = self.high_lats;
+		method.moonsighting = self.moonsighting;
+		method.tune = validate_tune(self.tune.unwrap_or_default())?;
+		Ok(method)
+	}
+}
+
+fn validate_angle(parameter: &'static str, angle: f64) -> Result<f64, Error> {
+	if (0.0..=90.0).contains(&angle) {
+		Ok(angle)
+	} else {
+		Err(Error::InvalidAngle(parameter, angle))
+	}
+}
+
+/// Tunes may be negative, but not infinite nor `NaN`
+fn validate_tune(tune: Tune) -> Result<Tune, Error> {
+	for (parameter, minutes) in [
+		("imsak tune", tune.imsak),
+		("fajr tune", tune.fajr),
+		("sunrise tune", tune.sunrise),
+		("dhuhr tune", tune.dhuhr),
+		("asr tune", tune.asr),
+		("sunset tune", tune.sunset),
+		("maghrib tune", tune.maghrib),
+		("isha tune", tune.isha),
+		("midnight tune", tune.midnight),
+	]
+	.iter()
+	{
+		if !minutes.is_finite() {
+			return Err(Error::InvalidMinutes(parameter, *minutes));
+		}
+	}
+	Ok(tune)
+}
+
+fn validate_minutes(parameter: &'static str, minutes: f64) -> Result<f64, Error> {
+	if minutes.is_finite() && minutes >= 0.0 {
+		Ok(minutes)
+	} else {
+		Err(Error::InvalidMinutes(parameter, minutes))
+	}
+}
+
+fn validate_type(parameter: &'static str, value: CalculationType) -> Result<f64, Error> {
+	match value {
+		CalculationType::Angle(angle) => validate_angle(parameter, angle),
+		CalculationType::Minutes(minutes) => validate_minutes(parameter, minutes),
+	}
 }
 
 /// The calculation methods
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CalculationMethods {
 	/// Muslim World League
 	MWL,
 	/// Islamic Society of North America
 	ISNA,
-	// Egyptian General Authority of Survey
+	/// Egyptian General Authority of Survey
 	Egypt,
 	/// Umm Al-Qura University, Makkah
 	///
@@ -104,6 +410,50 @@ pub enum CalculationMethods {
 	Jafari,
 	/// Muslims of France
 	MF,
+	/// Gulf region
+	Gulf,
+	/// Kuwait
+	Kuwait,
+	/// Qatar
+	Qatar,
+	/// Majlis Ugama Islam Singapura, Singapore
+	Singapore,
+	/// Jabatan Kemajuan Islam Malaysia (JAKIM)
+	Malaysia,
+	/// Kementerian Agama Republik Indonesia (KEMENAG)
+	Indonesia,
+	/// Diyanet İşleri Başkanlığı, Turkey
+	Turkey,
+	/// Dubai, United Arab Emirates
+	Dubai,
+	/// Spiritual Administration of Muslims of Russia
+	Russia,
+	/// Ministry of Religious Affairs and Wakfs, Algeria
+	Algeria,
+	/// Ministry of Religious Affairs, Tunisia
+	Tunisia,
+	/// Ministry of Habous and Islamic Affairs, Morocco
+	Morocco,
+	/// Ministry of Awqaf, Islamic Affairs and Holy Places, Jordan
+	Jordan,
+	/// Comunidade Islâmica de Lisboa, Portugal
+	Portugal,
+	/// Moonsighting Committee Worldwide
+	///
+	/// Fajr and isha are at 18°, but no earlier than a seasonal twilight before sunrise (fajr)
+	/// and no later than a seasonal twilight after sunset (isha), which depend on the latitude and
+	/// the days since the winter solstice. Above 55° of latitude, fajr and isha are at the
+	/// last and first seventh of the night.
+	///
+	/// https://www.moonsighting.com/isha_fajr.html
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// CalculationMethods::MoonsightingCommittee(Shafaq::General);
+	/// ~~~~
+	MoonsightingCommittee(Shafaq),
 	/// *Custom parameters*
 	///
 	/// # Example
@@ -112,74 +462,517 @@ pub enum CalculationMethods {
 	///
 	/// CalculationMethods::Custom(CalculationMethod::from(12.0, CalculationType::Angle(13.0)));
 	/// CalculationMethods::Custom(CalculationMethod::new(
-	///		None,
-	///		13.0,
-	///		None,
-	///		Some(CalculationType::Angle(6.0)),
-	///		CalculationType::Angle(13.0),
-	///		Some(MidnightMethod::Jafari),
-	///	));
+	///     None,
+	///     13.0,
+	///     None,
+	///     Some(CalculationType::Angle(6.0)),
+	///     CalculationType::Angle(13.0),
+	///     Some(MidnightMethod::Jafari),
+	/// ));
 	/// ~~~~
 	Custom(CalculationMethod),
 }
 
+/// A prayer (or another time of [`PrayerTimes`](PrayerTimes))
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Prayer {
+	/// Imsak
+	Imsak,
+	/// Fajr
+	Fajr,
+	/// Sunrise
+	Sunrise,
+	/// Dhuhr
+	Dhuhr,
+	/// Asr
+	Asr,
+	/// Sunset
+	Sunset,
+	/// Maghrib
+	Maghrib,
+	/// Isha
+	Isha,
+	/// Middle of the night
+	Midnight,
+}
+
+impl Prayer {
+	/// Every prayer, in the order of the day
+	pub const ALL: [Prayer; 9] = [
+		Prayer::Imsak,
+		Prayer::Fajr,
+		Prayer::Sunrise,
+		Prayer::Dhuhr,
+		Prayer::Asr,
+		Prayer::Sunset,
+		Prayer::Maghrib,
+		Prayer::Isha,
+		Prayer::Midnight,
+	];
+
+	/// The name of the prayer, as the field of [`PrayerTimes`](PrayerTimes)
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// assert_eq!(Prayer::Fajr.name(), "fajr");
+	/// ~~~~
+	pub fn name(&self) -> &'static str {
+		match self {
+			Prayer::Imsak => "imsak",
+			Prayer::Fajr => "fajr",
+			Prayer::Sunrise => "sunrise",
+			Prayer::Dhuhr => "dhuhr",
+			Prayer::Asr => "asr",
+			Prayer::Sunset => "sunset",
+			Prayer::Maghrib => "maghrib",
+			Prayer::Isha => "isha",
+			Prayer::Midnight => "midnight",
+		}
+	}
+}
+
+/// The times starting a period of [`PrayerManager::current_prayer`](PrayerManager::current_prayer)
+const PERIOD_PRAYERS: [Prayer; 6] = [
+	Prayer::Fajr,
+	Prayer::Sunrise,
+	Prayer::Dhuhr,
+	Prayer::Asr,
+	Prayer::Maghrib,
+	Prayer::Isha,
+];
+
+/// A prayer, its start time and the time remaining relative to an instant
+///
+/// See [`PrayerManager::current_prayer`](PrayerManager::current_prayer)
+/// and [`PrayerManager::next_prayer`](PrayerManager::next_prayer).
+#[derive(PartialEq, Debug, Clone)]
+pub struct PrayerPeriod<Tz: TimeZone = Utc> {
+	/// The prayer
+	pub prayer: Prayer,
+	/// When the prayer starts
+	pub start: DateTime<Tz>,
+	/// Until the next prayer (current prayer) or until the prayer starts (next prayer)
+	pub remaining: Duration,
+}
+
+/// How a time was obtained, from the most to the least reliable
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeStatus {
+	/// Computed from the position of the sun
+	Computed,
+	/// The sun does not reach the angle (or the time is beyond the limit of the night):
+	/// the time is given by the method for higher latitudes
+	Adjusted,
+	/// The sun does not rise or set (polar day or night), or depends on such a time:
+	/// the time is given by the polar method
+	Synthesized,
+	/// The sun does not reach the angle (or stays below the horizon, for asr) and no method
+	/// for higher latitudes or polar method applies: the time is `NaN`
+	Undefined,
+}
+
 /// Represents prayer times
+///
+/// Times are fractional hours relative to the start of the requested UTC day by default
+/// (values may be negative or greater than 24 when they fall on the previous or next day).
+/// See [`PrayerManager::get_date_times`](PrayerManager::get_date_times) for timestamps.
+///
+/// Use [`status`](PrayerTimes::status) to tell computed times from adjusted or undefined ones.
 #[derive(PartialEq, Debug, Copy, Clone)]
-pub struct PrayerTimes {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrayerTimes<T = f64> {
 	/// Imsak
-	pub imsak: f64,
+	pub imsak: T,
 	/// Fajr
-	pub fajr: f64,
+	pub fajr: T,
 	/// Sunrise
-	pub sunrise: f64,
+	pub sunrise: T,
 	/// Dhur
-	pub dhuhr: f64,
+	pub dhuhr: T,
 	/// Asr
-	pub asr: f64,
+	pub asr: T,
 	/// Sunset
-	pub sunset: f64,
+	pub sunset: T,
 	/// Maghrif
-	pub maghrib: f64,
+	pub maghrib: T,
 	/// Isha
-	pub isha: f64,
+	pub isha: T,
 	/// Middle of the night
-	pub midnight: f64,
+	pub midnight: T,
+	/// Indexed by [`Prayer`](Prayer)
+	status: [TimeStatus; 9],
+}
+
+impl<T> PrayerTimes<T> {
+	/// How the time of a prayer was obtained
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None);
+	///
+	/// let a_date = Utc.ymd(2021, 6, 21);
+	/// let a_house = Coordinates(51.5073509, -0.1277583, 0.0);
+	/// let prayers = prayer_manager.get_times(a_date, a_house);
+	/// assert_eq!(prayers.status(Prayer::Fajr), TimeStatus::Undefined);
+	/// assert_eq!(prayers.status(Prayer::Sunrise), TimeStatus::Computed);
+	/// ~~~~
+	pub fn status(&self, prayer: Prayer) -> TimeStatus {
+		self.status[prayer as usize]
+	}
+
+	/// The time of a prayer
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None);
+	/// let prayers = prayer_manager.get_times(Utc.ymd(2021, 4, 12), Coordinates(46.0, 6.0, 0.0));
+	/// assert_eq!(prayers.get(Prayer::Fajr), &prayers.fajr);
+	/// ~~~~
+	pub fn get(&self, prayer: Prayer) -> &T {
+		match prayer {
+			Prayer::Imsak => &self.imsak,
+			Prayer::Fajr => &self.fajr,
+			Prayer::Sunrise => &self.sunrise,
+			Prayer::Dhuhr => &self.dhuhr,
+			Prayer::Asr => &self.asr,
+			Prayer::Sunset => &self.sunset,
+			Prayer::Maghrib => &self.maghrib,
+			Prayer::Isha => &self.isha,
+			Prayer::Midnight => &self.midnight,
+		}
+	}
+
+	/// The prayers and their times sorted by a key, those without one last
+	fn sorted_by<K: PartialOrd, F: Fn(&T) -> Option<K>>(&self, key: F) -> Vec<(Prayer, &T)> {
+		let mut times: Vec<_> = Prayer::ALL
+			.iter()
+			.map(|&prayer| (prayer, self.get(prayer)))
+			.collect();
+		times.sort_by(|(_, a), (_, b)| match (key(a), key(b)) {
+			(Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(None, None) => Ordering::Equal,
+		});
+		times
+	}
+
+	/// Convert each time with the given function
+	pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> PrayerTimes<U> {
+		PrayerTimes {
+			status: self.status,
+			imsak: f(self.imsak),
+			fajr: f(self.fajr),
+			sunrise: f(self.sunrise),
+			dhuhr: f(self.dhuhr),
+			asr: f(self.asr),
+			sunset: f(self.sunset),
+			maghrib: f(self.maghrib),
+			isha: f(self.isha),
+			midnight: f(self.midnight),
+		}
+	}
+}
+
+impl PrayerTimes {
+	/// Iterate over the prayers and their times in chronological order, undefined times last
+	///
+	/// Prayers at the same time keep the order of [`Prayer::ALL`](Prayer::ALL).
+	pub fn iter(&self) -> impl Iterator<Item = (Prayer, &f64)> {
+		self
+			.sorted_by(|time| if time.is_nan() { None } else { Some(*time) })
+			.into_iter()
+	}
+
+	/// Anchor the fractional hours to a UTC date
+	///
+	/// Times before 0 or after 24 roll over to the previous or next day.
+	/// Undefined times (`NaN`) are `None`.
+	pub fn to_date_times(self, date: Date<Utc>) -> PrayerTimes<Option<DateTime<Utc>>> {
+		let start = date.and_hms(0, 0, 0);
+		self.map(|hours| {
+			if hours.is_finite() {
+				Some(start + Duration::milliseconds((hours * 3_600_000.0).round() as i64))
+			} else {
+				None
+			}
+		})
+	}
+}
+
+impl<Tz: TimeZone> PrayerTimes<Option<DateTime<Tz>>> {
+	/// Iterate over the prayers and their times in chronological order, undefined times last
+	///
+	/// Prayers at the same time keep the order of [`Prayer::ALL`](Prayer::ALL).
+	/// The middle of the night can come before isha, for example with
+	/// [`MidnightMethod::Jafari`](MidnightMethod::Jafari).
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None);
+	/// let prayers = prayer_manager.get_date_times(Utc.ymd(2021, 4, 12), Coordinates(46.0, 6.0, 0.0));
+	///
+	/// for (prayer, time) in prayers.iter() {
+	///     println!("{:?}: {:?}", prayer, time);
+	/// }
+	/// assert!(prayers.iter().all(|(_, time)| time.is_some()));
+	/// ~~~~
+	pub fn iter(&self) -> impl Iterator<Item = (Prayer, &Option<DateTime<Tz>>)> {
+		self.sorted_by(|time| time.clone()).into_iter()
+	}
 }
 
 /// The method to use for higher latitudes
 ///
 /// http://praytimes.org/calculation#Higher_Latitudes
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HightLatMethods {
 	/// Middle of the Night
 	///
 	/// > *In this method, the period from sunset to sunrise is divided into two halves.
-	/// The first half is considered to be the "night" and the other half as "day break".
-	/// Fajr and Isha in this method are assumed to be at mid-night during the abnormal periods.*
+	/// > The first half is considered to be the "night" and the other half as "day break".
+	/// > Fajr and Isha in this method are assumed to be at mid-night during the abnormal periods.*
+	///
 	/// http://praytimes.org/calculation#Higher_Latitudes
 	NightMiddle,
 	/// Angle-Based Method
 	///
 	/// > *This is an intermediate solution, used by some recent prayer time calculators.
-	/// Let α be the twilight angle for Isha, and let t = α/60.
-	/// The period between sunset and sunrise is divided into t parts.
-	/// Isha begins after the first part.
-	/// For example, if the twilight angle for Isha is 15, then Isha begins at the end of the first quarter (15/60) of the night.
-	/// Time for Fajr is calculated similarly.*
+	/// > Let α be the twilight angle for Isha, and let t = α/60.
+	/// > The period between sunset and sunrise is divided into t parts.
+	/// > Isha begins after the first part.
+	/// > For example, if the twilight angle for Isha is 15, then Isha begins at the end of the first quarter (15/60) of the night.
+	/// > Time for Fajr is calculated similarly.*
+	///
 	/// http://praytimes.org/calculation#Higher_Latitudes
 	AngleBased,
 	/// One-Seventh of the Night
 	///
 	/// > *In this method, the period between sunset and sunrise is divided into seven parts.
-	/// Isha begins after the first one-seventh part, and Fajr is at the beginning of the seventh part.*
+	/// > Isha begins after the first one-seventh part, and Fajr is at the beginning of the seventh part.*
+	///
 	/// http://praytimes.org/calculation#Higher_Latitudes
 	OneSeventh,
+	/// Nearest Latitude
+	///
+	/// When the sun does not reach the angle, the time is the one at the given latitude
+	/// (commonly 48.5° or 45°) on the same longitude and day.
+	NearestLatitude(f64),
+	/// Nearest Good Day
+	///
+	/// When the sun does not reach the angle, the time is the one of the last day it did.
+	NearestGoodDay,
+	/// Aqrab al-Bilad (nearest city)
+	///
+	/// When the sun does not reach the angle, the time divides the night in the same proportion
+	/// as at the given latitude (commonly 48.5° or 45°) on the same longitude and day.
+	AqrabAlBilad(f64),
+}
+
+/// The method to use when the sun does not rise or set (polar night or polar day)
+///
+/// Sunrise, sunset and asr are synthesized by the method, then fajr and isha can be adjusted
+/// from the synthesized night by the method for higher latitudes.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PolarMethods {
+	/// Times of the nearest day (before or after) when the sun rises, sets and reaches
+	/// the shadow of asr, relative to the solar noon
+	NearestDay,
+	/// Times at the given latitude (for example 65°) on the same longitude and day
+	ReferenceLatitude(f64),
+}
+
+impl PolarMethods {
+	/// The day and coordinates to take the missing times of a day from, where `defined`
+	/// holds (sunrise, sunset and asr all happen)
+	fn reference<F: Fn(Date<Utc>, Coordinates) -> bool>(
+		&self,
+		date: Date<Utc>,
+		coords: Coordinates,
+		defined: F,
+	) -> Option<(Date<Utc>, Coordinates)> {
+		match *self {
+			PolarMethods::NearestDay => (1..=183)
+				.flat_map(|days| [date - Duration::days(days), date + Duration::days(days)])
+				.find(|day| defined(*day, coords))
+				.map(|day| (day, coords)),
+			PolarMethods::ReferenceLatitude(latitude) => {
+				let latitude = latitude.min(coords.0.abs()).copysign(coords.0);
+				Some((date, Coordinates(latitude, coords.1, coords.2)))
+			}
+		}
+	}
+}
+
+/// How the elevation of the coordinates changes sunrise and sunset
+///
+/// From above the surrounding land, the horizon is lower and the sun rises earlier and sets
+/// later. Authorities differ on this correction, and on a plateau (like Tehran or Addis Ababa)
+/// the horizon is about as high as the observer.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ElevationMethod {
+	/// Compute the times at sea level
+	Ignore,
+	/// Lower the horizon as seen from the elevation above the sea level (the default)
+	Dip,
+	/// Lower the horizon as seen from the elevation above a horizon at the given elevation
+	/// (in meters)
+	Horizon(f64),
+}
+
+impl ElevationMethod {
+	/// The angle of the horizon below an observer at an elevation
+	fn dip(&self, elevation: f64) -> f64 {
+		match *self {
+			ElevationMethod::Ignore => 0.0,
+			ElevationMethod::Dip => dip(elevation, 0.0),
+			ElevationMethod::Horizon(horizon) => dip(elevation, horizon),
+		}
+	}
+}
+
+/// The night used to adjust times for higher latitudes
+struct Night {
+	date: Date<Utc>,
+	coords: Coordinates,
+	/// The angle of the sun at sunrise and sunset
+	horizon: f64,
+	sunrise: f64,
+	sunset: f64,
+}
+
+impl Night {
+	fn duration(&self) -> f64 {
+		time_diff(self.sunset, self.sunrise)
+	}
+}
+
+/// The solar noon, in hours relative to the start of the UTC day
+fn noon_time<E: Ephemeris>(ephemeris: &E, date: Date<Utc>, coords: Coordinates) -> f64 {
+	let julian_day = ephemeris.julian_day(&date) - coords.1 / (15.0 * 24.0);
+	mid_day(ephemeris, julian_day, 12.0 / 24.0) - coords.1 / 15.0
+}
+
+/// The time the sun reaches an angle, in hours relative to the start of the UTC day
+fn angle_time<E: Ephemeris>(
+	ephemeris: &E,
+	date: Date<Utc>,
+	coords: Coordinates,
+	angle: f64,
+	estimate: f64,
+	ccw: bool,
+) -> f64 {
+	let julian_day = ephemeris.julian_day(&date) - coords.1 / (15.0 * 24.0);
+	sun_angle_time(ephemeris, julian_day, coords.0, angle, estimate / 24.0, ccw) - coords.1 / 15.0
 }
 
 fn time_diff(time1: f64, time2: f64) -> f64 {
 	dmath::fix_hour(time2 - time1)
 }
 
+/// Whether a method for higher latitudes replaced `time`
+fn is_adjusted(time: f64, adjusted: f64) -> bool {
+	time.is_nan() != adjusted.is_nan() || (!time.is_nan() && time != adjusted)
+}
+
+fn time_status(time: f64, status: TimeStatus) -> TimeStatus {
+	if time.is_finite() {
+		status
+	} else {
+		TimeStatus::Undefined
+	}
+}
+
+fn finite_or(time: f64, default: f64) -> f64 {
+	if time.is_finite() {
+		time
+	} else {
+		default
+	}
+}
+
+impl PrayerTimes {
+	/// Rough estimates of the times in local solar hours, to start the iterations from
+	fn estimates() -> PrayerTimes {
+		PrayerTimes {
+			status: [TimeStatus::Computed; 9],
+			imsak: 5.0,
+			fajr: 5.0,
+			sunrise: 6.0,
+			dhuhr: 12.0,
+			asr: 13.0,
+			sunset: 18.0,
+			maghrib: 18.0,
+			isha: 18.0,
+			// computed from the other times
+			midnight: 0.0,
+		}
+	}
+
+	/// The estimates for the next pass, keeping the previous estimate of undefined times
+	fn refine(&self, estimates: &PrayerTimes) -> PrayerTimes {
+		PrayerTimes {
+			status: estimates.status,
+			imsak: finite_or(self.imsak, estimates.imsak),
+			fajr: finite_or(self.fajr, estimates.fajr),
+			sunrise: finite_or(self.sunrise, estimates.sunrise),
+			dhuhr: finite_or(self.dhuhr, estimates.dhuhr),
+			asr: finite_or(self.asr, estimates.asr),
+			sunset: finite_or(self.sunset, estimates.sunset),
+			maghrib: finite_or(self.maghrib, estimates.maghrib),
+			isha: finite_or(self.isha, estimates.isha),
+			midnight: estimates.midnight,
+		}
+	}
+
+	/// Whether every time differs from `other` by less than `tolerance` (undefined times are ignored)
+	fn converged(&self, other: &PrayerTimes, tolerance: f64) -> bool {
+		let close = |a: f64, b: f64| !a.is_finite() || !b.is_finite() || (a - b).abs() < tolerance;
+
+		close(self.imsak, other.imsak)
+			&& close(self.fajr, other.fajr)
+			&& close(self.sunrise, other.sunrise)
+			&& close(self.dhuhr, other.dhuhr)
+			&& close(self.asr, other.asr)
+			&& close(self.sunset, other.sunset)
+			&& close(self.maghrib, other.maghrib)
+			&& close(self.isha, other.isha)
+	}
+}
+
+/// The number of passes used to compute prayer times
+///
+/// Each pass evaluates the sun position (declination and equation of time) at the times
+/// found by the previous pass, the first one starting from rough estimates.
+///
+/// http://praytimes.org/calculation#Calculation_Procedure
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Iterations {
+	/// A fixed number of passes (at least one, the default is one)
+	Fixed(u32),
+	/// Passes until no time changes by more than `tolerance` seconds, up to `max` passes
+	Converge {
+		/// in seconds
+		tolerance: f64,
+		/// maximum number of passes
+		max: u32,
+	},
+}
+
 /// The prayer manager
 ///
 /// # Example
@@ -193,16 +986,29 @@ fn time_diff(time1: f64, time2: f64) -> f64 {
 /// let prayers = prayer_manager.get_times(a_date, a_house);
 /// ~~~~
 #[derive(PartialEq, Debug, Copy, Clone)]
-pub struct PrayerManager {
+pub struct PrayerManager<E = Usno> {
 	method: CalculationMethod,
 	high_lats: Option<HightLatMethods>,
+	polar: Option<PolarMethods>,
+	iterations: Iterations,
+	elevation: ElevationMethod,
+	twilight_elevation: bool,
+	ephemeris: E,
 }
 impl PrayerManager {
 	/// Initialize a PrayerManager
+	///
+	/// Without `high_lats`, the default of the calculation method (if any) is used.
 	pub fn new(method: CalculationMethods, high_lats: Option<HightLatMethods>) -> PrayerManager {
+		let method = PrayerManager::get_calculation_method(method);
 		PrayerManager {
-			method: PrayerManager::get_calculation_method(method),
-			high_lats,
+			method,
+			high_lats: high_lats.or(method.high_lats),
+			polar: None,
+			iterations: Iterations::Fixed(1),
+			elevation: ElevationMethod::Dip,
+			twilight_elevation: false,
+			ephemeris: Usno,
 		}
 	}
 
@@ -234,12 +1040,176 @@ impl PrayerManager {
 				Some(MidnightMethod::Jafari),
 			),
 			CalculationMethods::MF => CalculationMethod::from(12.0, CalculationType::Angle(12.0)),
+			CalculationMethods::Gulf => CalculationMethod::from(19.5, CalculationType::Minutes(90.0)),
+			CalculationMethods::Kuwait => CalculationMethod::from(18.0, CalculationType::Angle(17.5)),
+			CalculationMethods::Qatar => CalculationMethod::from(18.0, CalculationType::Minutes(90.0)),
+			CalculationMethods::Singapore
+			| CalculationMethods::Malaysia
+			| CalculationMethods::Indonesia => CalculationMethod::from(20.0, CalculationType::Angle(18.0)),
+			CalculationMethods::Turkey => CalculationMethod::from(18.0, CalculationType::Angle(17.0))
+				.with_tune(Tune {
+					sunrise: -7.0,
+					dhuhr: 5.0,
+					asr: 4.0,
+					maghrib: 7.0,
+					..Tune::default()
+				}),
+			CalculationMethods::Dubai => CalculationMethod::from(18.2, CalculationType::Angle(18.2))
+				.with_tune(Tune {
+					sunrise: -3.0,
+					dhuhr: 3.0,
+					asr: 3.0,
+					maghrib: 3.0,
+					..Tune::default()
+				}),
+			CalculationMethods::Russia => CalculationMethod::from(16.0, CalculationType::Angle(15.0)),
+			CalculationMethods::Algeria => CalculationMethod::from(18.0, CalculationType::Angle(17.0)),
+			CalculationMethods::Tunisia => CalculationMethod::from(18.0, CalculationType::Angle(18.0)),
+			CalculationMethods::Morocco => CalculationMethod::from(19.0, CalculationType::Angle(17.0)),
+			CalculationMethods::Jordan => CalculationMethod::new(
+				None,
+				18.0,
+				None,
+				Some(CalculationType::Minutes(5.0)),
+				CalculationType::Angle(18.0),
+				None,
+			),
+			CalculationMethods::Portugal => CalculationMethod::new(
+				None,
+				18.0,
+				None,
+				Some(CalculationType::Minutes(3.0)),
+				CalculationType::Minutes(77.0),
+				None,
+			),
+			CalculationMethods::MoonsightingCommittee(shafaq) => CalculationMethod {
+				moonsighting: Some(shafaq),
+				dhuhr: 5.0,
+				..CalculationMethod::new(
+					None,
+					18.0,
+					None,
+					Some(CalculationType::Minutes(3.0)),
+					CalculationType::Angle(18.0),
+					None,
+				)
+			},
 			CalculationMethods::Custom(value) => value,
 		}
 	}
+}
+
+impl<E: Ephemeris> PrayerManager<E> {
+	/// Set the method to use when the sun does not rise or set
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::OneSeventh))
+	///   .with_polar(PolarMethods::NearestDay);
+	///
+	/// let a_date = Utc.ymd(2021, 12, 21);
+	/// let tromso = Coordinates(69.6492047, 18.9553238, 0.0);
+	/// let prayers = prayer_manager.get_times(a_date, tromso);
+	/// assert_eq!(prayers.status(Prayer::Sunrise), TimeStatus::Synthesized);
+	/// assert!(prayers.sunrise.is_finite() && prayers.isha.is_finite());
+	/// ~~~~
+	pub fn with_polar(mut self, polar: PolarMethods) -> PrayerManager<E> {
+		self.polar = Some(polar);
+		self
+	}
+
+	/// Set the number of passes used to compute prayer times
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::AngleBased))
+	///   .with_iterations(Iterations::Converge { tolerance: 0.1, max: 10 });
+	/// ~~~~
+	pub fn with_iterations(mut self, iterations: Iterations) -> PrayerManager<E> {
+		self.iterations = iterations;
+		self
+	}
+
+	/// Set the minutes added to each computed time, replacing those of the calculation method
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None).with_tune(Tune {
+	///     dhuhr: 2.0,
+	///     maghrib: 3.0,
+	///     ..Tune::default()
+	/// });
+	/// ~~~~
+	pub fn with_tune(mut self, tune: Tune) -> PrayerManager<E> {
+		self.method.tune = tune;
+		self
+	}
+
+	/// Set how the elevation of the coordinates changes sunrise and sunset
+	/// ([`ElevationMethod::Dip`](ElevationMethod::Dip) by default)
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::Tehran, None);
+	/// let a_date = Utc.ymd(2021, 4, 12);
+	/// let tehran = Coordinates(35.6891975, 51.3889736, 1190.0);
+	/// let dip = prayer_manager.get_times(a_date, tehran);
+	///
+	/// // the plains around are about as high as the city
+	/// let plateau = prayer_manager
+	///   .with_elevation(ElevationMethod::Horizon(1100.0))
+	///   .get_times(a_date, tehran);
+	/// assert!(plateau.sunrise > dip.sunrise && plateau.sunset < dip.sunset);
+	/// ~~~~
+	pub fn with_elevation(mut self, elevation: ElevationMethod) -> PrayerManager<E> {
+		self.elevation = elevation;
+		self
+	}
+
+	/// Set whether the elevation also lowers the angles of imsak, fajr, maghrib and isha
+	/// (not by default)
+	///
+	/// The sun is then as far below the lowered horizon as the angles of the calculation
+	/// method are below the horizon at sea level.
+	pub fn with_twilight_elevation(mut self, twilight_elevation: bool) -> PrayerManager<E> {
+		self.twilight_elevation = twilight_elevation;
+		self
+	}
+
+	/// Set the ephemeris giving the position of the sun ([`Usno`](Usno) by default)
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, None)
+	///   .with_ephemeris(Meeus::new().with_delta_t(69.2));
+	/// ~~~~
+	pub fn with_ephemeris<F: Ephemeris>(self, ephemeris: F) -> PrayerManager<F> {
+		PrayerManager {
+			method: self.method,
+			high_lats: self.high_lats,
+			polar: self.polar,
+			iterations: self.iterations,
+			elevation: self.elevation,
+			twilight_elevation: self.twilight_elevation,
+			ephemeris,
+		}
+	}
 
 	/// Get prayer times for a specific UTC date and coordinates
 	///
+	/// The coordinates can come with the weather as an [`Observer`](Observer), which changes
+	/// the refraction at sunrise and sunset (and so the times based on them).
+	///
 	/// # Example
 	/// ~~~~
 	/// use prayers::*;
@@ -249,99 +1219,552 @@ impl PrayerManager {
 	/// let a_date = Utc.ymd(2021, 4, 12);
 	/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
 	/// let prayers = prayer_manager.get_times(a_date, a_house);
+	///
+	/// let a_hot_day = Observer::new(a_house).with_temperature(40.0)?;
+	/// assert!(prayer_manager.get_times(a_date, a_hot_day).sunrise > prayers.sunrise);
+	/// # Ok::<(), Error>(())
 	/// ~~~~
-	pub fn get_times(&self, date: Date<Utc>, coords: Coordinates) -> PrayerTimes {
-		let julian_day = get_julian_day(&date) - coords.1 / (15.0 * 24.0);
+	pub fn get_times(&self, date: Date<Utc>, observer: impl Into<Observer>) -> PrayerTimes {
+		self.get_times_from(date, observer.into(), &mut PrayerTimes::estimates())
+	}
+
+	/// Get prayer times, starting the iterations from the given estimates
+	///
+	/// With [`Iterations::Converge`](Iterations::Converge), the estimates are replaced by the
+	/// converged times, so that the next day starts close to its solution.
+	fn get_times_from(
+		&self,
+		date: Date<Utc>,
+		observer: Observer,
+		initial_estimates: &mut PrayerTimes,
+	) -> PrayerTimes {
+		let coords = observer.coords;
+		let dip = self.elevation.dip(coords.2);
+		let horizon = observer.rise_set_angle(dip);
+		let twilight_dip = if self.twilight_elevation { dip } else { 0.0 };
+		let ephemeris = &self.ephemeris;
+		let julian_day = ephemeris.julian_day(&date) - coords.1 / (15.0 * 24.0);
 		let method = &self.method;
 		let adjust = coords.1 / 15.0;
 
-		let mut imsak = sun_angle_time(
-			julian_day,
-			coords.0,
-			method.imsak.unwrap(),
-			5.0 / 24.0,
-			true,
-		) - adjust;
-
-		let mut fajr = sun_angle_time(julian_day, coords.0, method.fajr, 5.0 / 24.0, true) - adjust;
-
-		let sunrise = sun_angle_time(
-			julian_day,
-			coords.0,
-			rise_set_angle(coords.2),
-			6.0 / 24.0,
-			true,
-		) - adjust;
-
-		let dhuhr = mid_day(julian_day, 12.0 / 24.0) - adjust + method.dhuhr / 60.0;
-
-		let asr = PrayerManager::asr_time(julian_day, coords.0, &method.asr, 13.0 / 24.0) - adjust;
-
-		let sunset = sun_angle_time(
-			julian_day,
-			coords.0,
-			rise_set_angle(coords.2),
-			18.0 / 24.0,
-			false,
-		) - adjust;
-
-		let mut maghrib = sun_angle_time(
-			julian_day,
-			coords.0,
-			method.maghrib.unwrap(),
-			18.0 / 24.0,
-			false,
-		) - adjust;
-
-		let mut isha = sun_angle_time(
-			julian_day,
-			coords.0,
-			method.isha.unwrap(),
-			18.0 / 24.0,
-			false,
-		) - adjust;
+		let mut estimates = *initial_estimates;
+		let (passes, tolerance) = match self.iterations {
+			Iterations::Fixed(passes) => (passes.max(1), None),
+			Iterations::Converge { tolerance, max } => (max.max(1), Some(tolerance / 3600.0)),
+		};
+
+		let mut times = self.compute_times(julian_day, coords, horizon, twilight_dip, &estimates);
+		for _ in 1..passes {
+			estimates = times.refine(&estimates);
+			let previous = times;
+			times = self.compute_times(julian_day, coords, horizon, twilight_dip, &estimates);
+
+			if let Some(tolerance) = tolerance {
+				if previous.converged(&times, tolerance) {
+					break;
+				}
+			}
+		}
+		if tolerance.is_some() {
+			*initial_estimates = times.refine(&estimates);
+		}
+
+		let mut imsak = times.imsak - adjust;
+		let mut fajr = times.fajr - adjust;
+		let mut sunrise = times.sunrise - adjust;
+		let dhuhr = times.dhuhr - adjust + method.dhuhr / 60.0;
+		let mut asr = times.asr - adjust;
+		let mut sunset = times.sunset - adjust;
+		let mut maghrib = times.maghrib - adjust;
+		let mut isha = times.isha - adjust;
+
+		let (mut imsak_status, mut fajr_status) = (TimeStatus::Computed, TimeStatus::Computed);
+		let (mut sunrise_status, mut asr_status) = (TimeStatus::Computed, TimeStatus::Computed);
+		let (mut sunset_status, mut maghrib_status) = (TimeStatus::Computed, TimeStatus::Computed);
+		let mut isha_status = TimeStatus::Computed;
+
+		let sunrise_time = |date: Date<Utc>, coords: Coordinates| {
+			angle_time(ephemeris, date, coords, horizon, 6.0, true)
+		};
+		let sunset_time = |date: Date<Utc>, coords: Coordinates| {
+			angle_time(ephemeris, date, coords, horizon, 18.0, false)
+		};
+		let asr_time = |date: Date<Utc>, coords: Coordinates| {
+			let julian_day = ephemeris.julian_day(&date) - coords.1 / (15.0 * 24.0);
+			// the shadow of asr is meaningless when the sun stays below the horizon
+			if (coords.0 - ephemeris.sun_position(julian_day + 0.5).0).abs() > 90.0 {
+				return f64::NAN;
+			}
+			self.asr_time(julian_day, coords.0, &method.asr, 13.0 / 24.0) - coords.1 / 15.0
+		};
+		if asr_time(date, coords).is_nan() {
+			asr = f64::NAN;
+		}
+
+		if let Some(polar) = self.polar {
+			let reference = if sunrise.is_nan() || sunset.is_nan() || asr.is_nan() {
+				polar.reference(date, coords, |date, coords| {
+					!sunrise_time(date, coords).is_nan()
+						&& !sunset_time(date, coords).is_nan()
+						&& !asr_time(date, coords).is_nan()
+				})
+			} else {
+				None
+			};
+
+			// every missing time from the same day, relative to the solar noon
+			if let Some((day, reference)) = reference {
+				let shift = noon_time(ephemeris, date, coords) - noon_time(ephemeris, day, reference);
+				if sunrise.is_nan() {
+					sunrise = sunrise_time(day, reference) + shift;
+					sunrise_status = TimeStatus::Synthesized;
+				}
+				if sunset.is_nan() {
+					sunset = sunset_time(day, reference) + shift;
+					sunset_status = TimeStatus::Synthesized;
+				}
+				if asr.is_nan() {
+					asr = asr_time(day, reference) + shift;
+					asr_status = TimeStatus::Synthesized;
+				}
+			}
+		}
 
 		if self.high_lats.is_some() {
-			let night_time = time_diff(sunset, sunrise);
+			let night = Night {
+				date,
+				coords,
+				horizon,
+				sunrise,
+				sunset,
+			};
+
+			let adjusted =
+				self.adjust_highlat_time(imsak, &night, method.imsak.unwrap() + twilight_dip, true);
+			if is_adjusted(imsak, adjusted) {
+				imsak = adjusted;
+				imsak_status = TimeStatus::Adjusted;
+			}
+			let adjusted = self.adjust_highlat_time(fajr, &night, method.fajr + twilight_dip, true);
+			if is_adjusted(fajr, adjusted) {
+				fajr = adjusted;
+				fajr_status = TimeStatus::Adjusted;
+			}
+			let adjusted = self.adjust_highlat_time(
+				maghrib,
+				&night,
+				method.maghrib.unwrap() + twilight_dip,
+				false,
+			);
+			if is_adjusted(maghrib, adjusted) {
+				maghrib = adjusted;
+				maghrib_status = TimeStatus::Adjusted;
+			}
+			let adjusted =
+				self.adjust_highlat_time(isha, &night, method.isha.unwrap() + twilight_dip, false);
+			if is_adjusted(isha, adjusted) {
+				isha = adjusted;
+				isha_status = TimeStatus::Adjusted;
+			}
+		}
 
-			imsak = self.adjust_highlat_time(imsak, sunrise, method.imsak.unwrap(), night_time, true);
-			fajr = self.adjust_highlat_time(fajr, sunrise, method.fajr, night_time, true);
-			maghrib =
-				self.adjust_highlat_time(maghrib, sunset, method.maghrib.unwrap(), night_time, false);
-			isha = self.adjust_highlat_time(isha, sunset, method.isha.unwrap(), night_time, false);
+		if let Some(shafaq) = method.moonsighting {
+			if coords.0.abs() >= 55.0 {
+				let night_time = time_diff(sunset, sunrise);
+				fajr = sunrise - night_time / 7.0;
+				isha = sunset + night_time / 7.0;
+				fajr_status = TimeStatus::Adjusted;
+				isha_status = TimeStatus::Adjusted;
+			} else {
+				let safe_fajr = sunrise - moonsighting::morning_twilight(coords.0, &date) / 60.0;
+				if fajr.is_nan() || safe_fajr > fajr {
+					fajr = safe_fajr;
+					fajr_status = sunrise_status;
+				}
+				let safe_isha = sunset + moonsighting::evening_twilight(coords.0, &date, shafaq) / 60.0;
+				if isha.is_nan() || safe_isha < isha {
+					isha = safe_isha;
+					isha_status = sunset_status;
+				}
+			}
 		}
 
 		if let CalculationType::Minutes(minutes) = method.imsak {
 			imsak = fajr - minutes / 60.0;
+			imsak_status = fajr_status;
 		}
 		if let CalculationType::Minutes(minutes) = method.maghrib {
-			maghrib = sunset - minutes / 60.0;
+			maghrib = sunset + minutes / 60.0;
+			maghrib_status = sunset_status;
 		}
 		if let CalculationType::Minutes(minutes) = method.isha {
-			isha = maghrib - minutes / 60.0;
+			isha = maghrib + minutes / 60.0;
+			isha_status = maghrib_status;
 		}
 
-		let midnight = sunset
-			+ match method.midnight {
-				MidnightMethod::Standard => time_diff(sunset, sunrise),
-				MidnightMethod::Jafari => time_diff(sunset, fajr),
-			} / 2.0;
+		let (midnight, midnight_status) = match method.midnight {
+			MidnightMethod::Standard => (
+				sunset + time_diff(sunset, sunrise) / 2.0,
+				sunset_status.max(sunrise_status),
+			),
+			MidnightMethod::Jafari => (
+				sunset + time_diff(sunset, fajr) / 2.0,
+				sunset_status.max(fajr_status),
+			),
+		};
 
+		let tune = &method.tune;
 		PrayerTimes {
-			imsak,
-			fajr,
-			sunrise,
-			dhuhr,
-			asr,
-			sunset,
-			maghrib,
-			isha,
-			midnight,
+			imsak: imsak + tune.imsak / 60.0,
+			fajr: fajr + tune.fajr / 60.0,
+			sunrise: sunrise + tune.sunrise / 60.0,
+			dhuhr: dhuhr + tune.dhuhr / 60.0,
+			asr: asr + tune.asr / 60.0,
+			sunset: sunset + tune.sunset / 60.0,
+			maghrib: maghrib + tune.maghrib / 60.0,
+			isha: isha + tune.isha / 60.0,
+			midnight: midnight + tune.midnight / 60.0,
+			status: [
+				time_status(imsak, imsak_status),
+				time_status(fajr, fajr_status),
+				time_status(sunrise, sunrise_status),
+				time_status(dhuhr, TimeStatus::Computed),
+				time_status(asr, asr_status),
+				time_status(sunset, sunset_status),
+				time_status(maghrib, maghrib_status),
+				time_status(isha, isha_status),
+				time_status(midnight, midnight_status),
+			],
 		}
 	}
 
-	fn adjust_highlat_time(&self, time: f64, base: f64, angle: f64, night: f64, ccw: bool) -> f64 {
-		let portion = self.night_portion(angle, night);
+	/// Get prayer times as UTC timestamps for a specific UTC date and coordinates
+	///
+	/// Times that cannot be computed are `None`.
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+	///
+	/// let a_date = Utc.ymd(2021, 4, 12);
+	/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+	/// let prayers = prayer_manager.get_date_times(a_date, a_house);
+	/// assert_eq!(prayers.isha.unwrap().date(), Utc.ymd(2021, 4, 13));
+	/// ~~~~
+	pub fn get_date_times(
+		&self,
+		date: Date<Utc>,
+		observer: impl Into<Observer>,
+	) -> PrayerTimes<Option<DateTime<Utc>>> {
+		self.get_times(date, observer).to_date_times(date)
+	}
+
+	/// Get prayer times as local timestamps for a local calendar date, time zone and coordinates
+	///
+	/// Each time is converted to the time zone on its own, so days with a DST transition are handled.
+	/// Times that cannot be computed are `None`.
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+	///
+	/// let a_date = NaiveDate::from_ymd(2021, 4, 12);
+	/// let a_zone = FixedOffset::west(4 * 3600);
+	/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+	/// let prayers = prayer_manager.get_local_times(a_date, &a_zone, a_house);
+	/// assert_eq!(prayers.isha.unwrap().date().naive_local(), a_date);
+	/// ~~~~
+	pub fn get_local_times<Tz: TimeZone>(
+		&self,
+		date: NaiveDate,
+		tz: &Tz,
+		observer: impl Into<Observer>,
+	) -> PrayerTimes<Option<DateTime<Tz>>> {
+		self.get_local_times_from(date, tz, observer.into(), &mut PrayerTimes::estimates())
+	}
+
+	/// Get prayer times as local timestamps, starting the iterations from the given estimates
+	fn get_local_times_from<Tz: TimeZone>(
+		&self,
+		date: NaiveDate,
+		tz: &Tz,
+		observer: Observer,
+		estimates: &mut PrayerTimes,
+	) -> PrayerTimes<Option<DateTime<Tz>>> {
+		let mut utc_date = Utc.from_utc_date(&date);
+		let mut times = self
+			.get_times_from(utc_date, observer, estimates)
+			.to_date_times(utc_date);
+
+		// The solar day is centered on the longitude: if the time zone is far from it,
+		// the computed day may fall on another local date.
+		if let Some(dhuhr) = times.dhuhr {
+			let shift = date.signed_duration_since(dhuhr.with_timezone(tz).naive_local().date());
+			if shift.num_days() != 0 {
+				utc_date += shift;
+				times = self
+					.get_times_from(utc_date, observer, estimates)
+					.to_date_times(utc_date);
+			}
+		}
+
+		times.map(|time| time.map(|time| time.with_timezone(tz)))
+	}
+
+	/// Get the prayer times of every day from `start` to `end` (inclusive), as timestamps
+	/// in the time zone of the dates
+	///
+	/// Each day is computed as with [`get_local_times`](PrayerManager::get_local_times),
+	/// so DST transitions are handled. With [`Iterations::Converge`](Iterations::Converge),
+	/// each day starts from the times of the previous day, which saves passes.
+	/// With [`Iterations::Fixed`](Iterations::Fixed) (the default), each day starts from
+	/// the same rough estimates, so that the times match `get_local_times`: the previous
+	/// day is not reused, and a timetable is no more precise than the days computed alone.
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+	///
+	/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+	/// let april = prayer_manager.timetable(Utc.ymd(2021, 4, 1), Utc.ymd(2021, 4, 30), a_house);
+	/// for (date, prayers) in april {
+	///     println!("{}: {:?}", date, prayers.fajr);
+	/// }
+	///
+	/// let a_zone = FixedOffset::west(4 * 3600);
+	/// let days: Vec<_> = prayer_manager
+	///     .timetable(a_zone.ymd(2021, 4, 1), a_zone.ymd(2021, 4, 30), a_house)
+	///     .collect();
+	/// assert_eq!(days.len(), 30);
+	/// assert_eq!(days[11].1, prayer_manager.get_local_times(NaiveDate::from_ymd(2021, 4, 12), &a_zone, a_house));
+	/// ~~~~
+	pub fn timetable<Tz: TimeZone>(
+		&self,
+		start: Date<Tz>,
+		end: Date<Tz>,
+		observer: impl Into<Observer>,
+	) -> Timetable<'_, Tz, E> {
+		Timetable {
+			manager: self,
+			date: Some(start),
+			end,
+			observer: observer.into(),
+			estimates: PrayerTimes::estimates(),
+		}
+	}
+
+	/// Get the current prayer at an instant (fajr, sunrise, dhuhr, asr, maghrib or isha),
+	/// with the time remaining until the next one
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+	///
+	/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+	/// let current = prayer_manager.current_prayer(Utc.ymd(2021, 4, 12).and_hms(20, 0, 0), a_house).unwrap();
+	/// assert_eq!(current.prayer, Prayer::Dhuhr);
+	/// assert_eq!(current.remaining.num_minutes(), 49);
+	/// ~~~~
+	pub fn current_prayer<Tz: TimeZone>(
+		&self,
+		at: DateTime<Tz>,
+		observer: impl Into<Observer>,
+	) -> Option<PrayerPeriod<Tz>> {
+		let times = self.prayers_around(&at, observer.into());
+		let next = times.iter().position(|(_, time)| *time > at)?;
+		let (prayer, start) = times.get(next.checked_sub(1)?)?;
+
+		Some(PrayerPeriod {
+			prayer: *prayer,
+			start: start.with_timezone(&at.timezone()),
+			remaining: times[next].1.signed_duration_since(at),
+		})
+	}
+
+	/// Get the next prayer after an instant (fajr, sunrise, dhuhr, asr, maghrib or isha),
+	/// with the time remaining until it starts
+	///
+	/// # Example
+	/// ~~~~
+	/// use prayers::*;
+	///
+	/// let prayer_manager = PrayerManager::new(CalculationMethods::MWL, Some(HightLatMethods::NightMiddle));
+	///
+	/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+	/// let next = prayer_manager.next_prayer(Utc.ymd(2021, 4, 12).and_hms(20, 0, 0), a_house).unwrap();
+	/// assert_eq!(next.prayer, Prayer::Asr);
+	/// assert_eq!(next.start, Utc.ymd(2021, 4, 12).and_hms_milli(20, 49, 53, 382));
+	/// ~~~~
+	pub fn next_prayer<Tz: TimeZone>(
+		&self,
+		at: DateTime<Tz>,
+		observer: impl Into<Observer>,
+	) -> Option<PrayerPeriod<Tz>> {
+		let times = self.prayers_around(&at, observer.into());
+		let (prayer, start) = times.into_iter().find(|(_, time)| *time > at)?;
+
+		Some(PrayerPeriod {
+			prayer,
+			remaining: start.signed_duration_since(at.clone()),
+			start: start.with_timezone(&at.timezone()),
+		})
+	}
+
+	/// The defined times of the prayers of the day before, the day of and the day after an instant,
+	/// in chronological order
+	fn prayers_around<Tz: TimeZone>(
+		&self,
+		at: &DateTime<Tz>,
+		observer: Observer,
+	) -> Vec<(Prayer, DateTime<Utc>)> {
+		let date = at.with_timezone(&Utc).date();
+		let mut times = Vec::new();
+
+		for day in [date.pred(), date, date.succ()].iter() {
+			let day_times = self.get_date_times(*day, observer);
+			for prayer in PERIOD_PRAYERS.iter() {
+				if let Some(time) = *day_times.get(*prayer) {
+					times.push((*prayer, time));
+				}
+			}
+		}
+
+		times.sort_by_key(|(_, time)| *time);
+		times
+	}
+
+	/// Compute the times in local solar hours, evaluating the sun position at the estimated times
+	fn compute_times(
+		&self,
+		julian_day: f64,
+		coords: Coordinates,
+		horizon: f64,
+		twilight_dip: f64,
+		estimates: &PrayerTimes,
+	) -> PrayerTimes {
+		let method = &self.method;
+
+		PrayerTimes {
+			status: estimates.status,
+			imsak: sun_angle_time(
+				&self.ephemeris,
+				julian_day,
+				coords.0,
+				method.imsak.unwrap() + twilight_dip,
+				estimates.imsak / 24.0,
+				true,
+			),
+			fajr: sun_angle_time(
+				&self.ephemeris,
+				julian_day,
+				coords.0,
+				method.fajr + twilight_dip,
+				estimates.fajr / 24.0,
+				true,
+			),
+			sunrise: sun_angle_time(
+				&self.ephemeris,
+				julian_day,
+				coords.0,
+				horizon,
+				estimates.sunrise / 24.0,
+				true,
+			),
+			dhuhr: mid_day(&self.ephemeris, julian_day, estimates.dhuhr / 24.0),
+			asr: self.asr_time(julian_day, coords.0, &method.asr, estimates.asr / 24.0),
+			sunset: sun_angle_time(
+				&self.ephemeris,
+				julian_day,
+				coords.0,
+				horizon,
+				estimates.sunset / 24.0,
+				false,
+			),
+			maghrib: sun_angle_time(
+				&self.ephemeris,
+				julian_day,
+				coords.0,
+				method.maghrib.unwrap() + twilight_dip,
+				estimates.maghrib / 24.0,
+				false,
+			),
+			isha: sun_angle_time(
+				&self.ephemeris,
+				julian_day,
+				coords.0,
+				method.isha.unwrap() + twilight_dip,
+				estimates.isha / 24.0,
+				false,
+			),
+			midnight: estimates.midnight,
+		}
+	}
+
+	fn adjust_highlat_time(&self, time: f64, night: &Night, angle: f64, ccw: bool) -> f64 {
+		let base = if ccw { night.sunrise } else { night.sunset };
+		let estimate = if ccw { 5.0 } else { 18.0 };
+		let portion = match self.high_lats.unwrap() {
+			HightLatMethods::NightMiddle => 1.0 / 2.0,
+			HightLatMethods::AngleBased => 1.0 / 60.0 * angle,
+			HightLatMethods::OneSeventh => 1.0 / 7.0,
+			_ if !time.is_nan() => return time,
+			HightLatMethods::NearestLatitude(latitude) => {
+				let latitude = latitude.min(night.coords.0.abs()).copysign(night.coords.0);
+				let coords = Coordinates(latitude, night.coords.1, night.coords.2);
+				return angle_time(&self.ephemeris, night.date, coords, angle, estimate, ccw);
+			}
+			HightLatMethods::NearestGoodDay => {
+				return (1..=366)
+					.map(|days| {
+						angle_time(
+							&self.ephemeris,
+							night.date - Duration::days(days),
+							night.coords,
+							angle,
+							estimate,
+							ccw,
+						)
+					})
+					.find(|time| !time.is_nan())
+					.unwrap_or(time);
+			}
+			HightLatMethods::AqrabAlBilad(latitude) => {
+				let latitude = latitude.min(night.coords.0.abs()).copysign(night.coords.0);
+				let coords = Coordinates(latitude, night.coords.1, night.coords.2);
+				let sunrise = angle_time(
+					&self.ephemeris,
+					night.date,
+					coords,
+					night.horizon,
+					6.0,
+					true,
+				);
+				let sunset = angle_time(
+					&self.ephemeris,
+					night.date,
+					coords,
+					night.horizon,
+					18.0,
+					false,
+				);
+				let time = angle_time(&self.ephemeris, night.date, coords, angle, estimate, ccw);
+
+				(if ccw {
+					time_diff(time, sunrise)
+				} else {
+					time_diff(sunset, time)
+				}) / time_diff(sunset, sunrise)
+			}
+		} * night.duration();
+
 		let diff = if ccw {
 			time_diff(time, base)
 		} else {
@@ -355,22 +1778,67 @@ impl PrayerManager {
 		base + if ccw { -portion } else { portion }
 	}
 
-	fn asr_time(julian_day: f64, latitude: f64, factor_type: &AsrJuristic, time: f64) -> f64 {
-		let decl = sun_position(julian_day + time).0;
+	fn asr_time(&self, julian_day: f64, latitude: f64, factor_type: &AsrJuristic, time: f64) -> f64 {
+		let decl = self.ephemeris.sun_position(julian_day + time).0;
 		let factor = match factor_type {
 			AsrJuristic::Standard => 1.0,
 			AsrJuristic::Hanafi => 2.0,
 		};
 
 		let angle = -dmath::arccot(&(factor + dmath::tan(&(latitude - decl).abs())));
-		sun_angle_time(julian_day, latitude, angle, time, false)
+		sun_angle_time(&self.ephemeris, julian_day, latitude, angle, time, false)
+	}
+}
+
+/// An iterator over the prayer times of consecutive days
+///
+/// See [`PrayerManager::timetable`](PrayerManager::timetable).
+#[derive(Debug, Clone)]
+pub struct Timetable<'a, Tz: TimeZone, E = Usno> {
+	manager: &'a PrayerManager<E>,
+	/// The next day, `None` once past the end
+	date: Option<Date<Tz>>,
+	end: Date<Tz>,
+	observer: Observer,
+	/// Carried from one day to the next
+	estimates: PrayerTimes,
+}
+
+impl<'a, Tz: TimeZone, E> Timetable<'a, Tz, E> {
+	/// The coordinates of the timetable
+	pub fn coordinates(&self) -> Coordinates {
+		self.observer.coords
 	}
+}
 
-	fn night_portion(&self, angle: f64, night: f64) -> f64 {
-		(match self.high_lats.as_ref().unwrap() {
-			HightLatMethods::NightMiddle => 1.0 / 2.0,
-			HightLatMethods::AngleBased => 1.0 / 60.0 * angle,
-			HightLatMethods::OneSeventh => 1.0 / 7.0,
-		}) * night
+impl<'a, Tz: TimeZone, E: Ephemeris> Iterator for Timetable<'a, Tz, E> {
+	type Item = (Date<Tz>, PrayerTimes<Option<DateTime<Tz>>>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let date = self.date.take().filter(|date| *date <= self.end)?;
+		self.date = date.succ_opt();
+
+		let times = self.manager.get_local_times_from(
+			date.naive_local(),
+			&date.timezone(),
+			self.observer,
+			&mut self.estimates,
+		);
+		Some((date, times))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let days = match &self.date {
+			Some(date) if *date <= self.end => {
+				self
+					.end
+					.naive_local()
+					.signed_duration_since(date.naive_local())
+					.num_days() as usize
+					+ 1
+			}
+			_ => 0,
+		};
+		(days, Some(days))
 	}
 }
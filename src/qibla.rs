@@ -0,0 +1,280 @@
+use crate::astronomy::{julian_date, mid_day, Coordinates, SolarPosition};
+use crate::dmath;
+use crate::ephemeris::{Ephemeris, Usno};
+use chrono::{Date, DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+/// The coordinates of the Kaaba, in Makkah
+pub const KAABA: Coordinates = Coordinates(21.4225241, 39.8261818, 0.0);
+
+/// Mean radius of the Earth, in kilometres
+const EARTH_RADIUS: f64 = 6371.0088;
+
+/// The direction and distance of the Kaaba
+///
+/// Bearings are in degrees clockwise from the north (0 to 360°). At a pole, where every direction
+/// points south (or north), they are relative to the meridian of the longitude of the coordinates.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Qibla {
+	/// Initial bearing of the great circle (the shortest path), `None` at the Kaaba
+	/// and at its antipode, where every direction leads to the Kaaba
+	pub bearing: Option<f64>,
+	/// Great-circle distance, in kilometres
+	pub distance: f64,
+	/// Constant bearing of the rhumb line (loxodrome), `None` at the Kaaba
+	pub rhumb_bearing: Option<f64>,
+}
+
+/// Get the direction of and the distance to the Kaaba from coordinates
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let qibla = qibla(a_house);
+/// assert_eq!(format!("{:.1}°", qibla.bearing.unwrap()), "56.6°");
+/// assert_eq!(format!("{:.0} km", qibla.distance), "10633 km");
+/// ~~~~
+pub fn qibla(coords: Coordinates) -> Qibla {
+	let (latitude, kaaba_latitude) = (coords.0, KAABA.0);
+	let delta_longitude = KAABA.1 - coords.1;
+
+	// haversine, accurate for small distances
+	let a = dmath::sin(&((kaaba_latitude - latitude) / 2.0)).powi(2)
+		+ dmath::cos(&latitude)
+			* dmath::cos(&kaaba_latitude)
+			* dmath::sin(&(delta_longitude / 2.0)).powi(2);
+	let angle = 2.0 * a.sqrt().min(1.0).asin();
+	let distance = EARTH_RADIUS * angle;
+
+	let tolerance = 1e-9;
+	if angle < tolerance {
+		return Qibla {
+			bearing: None,
+			distance,
+			rhumb_bearing: None,
+		};
+	}
+
+	let bearing = if angle > std::f64::consts::PI - tolerance {
+		None
+	} else {
+		Some(dmath::fix_angle(dmath::arctan2(
+			&(dmath::sin(&delta_longitude) * dmath::cos(&kaaba_latitude)),
+			&(dmath::cos(&latitude) * dmath::sin(&kaaba_latitude)
+				- dmath::sin(&latitude) * dmath::cos(&kaaba_latitude) * dmath::cos(&delta_longitude)),
+		)))
+	};
+
+	Qibla {
+		bearing,
+		distance,
+		rhumb_bearing: Some(rhumb_bearing(latitude, kaaba_latitude, delta_longitude)),
+	}
+}
+
+/// Bearing of the rhumb line between two latitudes separated by a longitude difference
+fn rhumb_bearing(from: f64, to: f64, delta_longitude: f64) -> f64 {
+	// the Mercator projection stretches to infinity at the poles
+	if from >= 90.0 {
+		return 180.0;
+	}
+	if from <= -90.0 {
+		return 0.0;
+	}
+
+	// the shorter way around
+	let delta_longitude = dmath::fix_angle(delta_longitude + 180.0) - 180.0;
+	let projected = |latitude: f64| dmath::tan(&(45.0 + latitude / 2.0)).ln();
+
+	dmath::fix_angle(dmath::arctan2(
+		&delta_longitude.to_radians(),
+		&(projected(to) - projected(from)),
+	))
+}
+
+/// Where the sun is relative to the Qibla
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum QiblaDirection {
+	/// The sun is in the direction of the Qibla: shadows point away from it
+	Sun,
+	/// The sun is opposite to the Qibla: shadows point toward it
+	Shadow,
+}
+
+/// A moment the azimuth of the sun gives the Qibla
+///
+/// See [`qibla_times`](qibla_times).
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct QiblaTime {
+	/// When the azimuth of the sun is the bearing of the Qibla, or its opposite
+	pub time: DateTime<Utc>,
+	/// Whether the sun or the shadows point to the Qibla
+	pub direction: QiblaDirection,
+}
+
+/// Get the moments of a day when the sun, above the horizon, is in the direction
+/// of the Qibla or opposite to it, so that vertical shadows show the Qibla
+///
+/// The day is the solar day centered on the mid-day of the UTC date at the longitude,
+/// as for [`PrayerManager::get_times`](crate::PrayerManager::get_times).
+/// The moments are in chronological order; there are none when the sun never has
+/// such an azimuth that day (or at the Kaaba and its antipode).
+/// Uses the [`Usno`](Usno) ephemeris, see [`qibla_times_with`](qibla_times_with).
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let times = qibla_times(a_house, Utc.ymd(2021, 4, 12));
+/// // the sun rises south of the Qibla, but shadows point to it in the afternoon
+/// assert_eq!(times.len(), 1);
+/// assert_eq!(times[0].direction, QiblaDirection::Shadow);
+/// ~~~~
+pub fn qibla_times(coords: Coordinates, date: Date<Utc>) -> Vec<QiblaTime> {
+	qibla_times_with(&Usno, coords, date)
+}
+
+/// Get the moments of a day when the sun or the shadows are in the direction of the Qibla,
+/// with an ephemeris (see [`qibla_times`](qibla_times))
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let a_house = Coordinates(38.8976763, -77.036529, 18.0);
+/// let times = qibla_times_with(&Meeus::new(), a_house, Utc.ymd(2021, 4, 12));
+/// assert_eq!(times[0].direction, QiblaDirection::Shadow);
+/// ~~~~
+pub fn qibla_times_with<E: Ephemeris>(
+	ephemeris: &E,
+	coords: Coordinates,
+	date: Date<Utc>,
+) -> Vec<QiblaTime> {
+	let bearing = match qibla(coords).bearing {
+		Some(bearing) => bearing,
+		None => return Vec::new(),
+	};
+	let julian_date = julian_date(&date);
+	let noon = mid_day(ephemeris, julian_date - coords.1 / (15.0 * 24.0), 0.5) - coords.1 / 15.0;
+	// azimuth relative to a direction, in -180 to 180°
+	let offset = |hours: f64, direction: f64| {
+		let sun = SolarPosition::at(ephemeris, julian_date, hours, coords);
+		(
+			sun.altitude,
+			dmath::fix_angle(sun.azimuth - direction + 180.0) - 180.0,
+		)
+	};
+
+	let mut times = Vec::new();
+	for (direction, angle) in [
+		(QiblaDirection::Sun, bearing),
+		(QiblaDirection::Shadow, bearing + 180.0),
+	]
+	.iter()
+	{
+		let step = 5.0 / 60.0;
+		let mut start = noon - 12.0;
+		let mut start_offset = offset(start, *angle).1;
+
+		while start < noon + 12.0 {
+			let end = start + step;
+			let end_offset = offset(end, *angle).1;
+
+			// a change of sign, not a wrap around the opposite direction
+			if start_offset.signum() != end_offset.signum() && (start_offset - end_offset).abs() < 180.0 {
+				let (mut low, mut high) = (start, end);
+				for _ in 0..40 {
+					let middle = (low + high) / 2.0;
+					if offset(middle, *angle).1.signum() == start_offset.signum() {
+						low = middle;
+					} else {
+						high = middle;
+					}
+				}
+				let time = (low + high) / 2.0;
+				if offset(time, *angle).0 > 0.0 {
+					times.push(QiblaTime {
+						time: to_date_time(date, time),
+						direction: *direction,
+					});
+				}
+			}
+
+			start = end;
+			start_offset = end_offset;
+		}
+	}
+
+	times.sort_by_key(|qibla_time| qibla_time.time);
+	times
+}
+
+/// Get the moments of a year when the sun passes over the Kaaba (at its zenith),
+/// at mid-day in Makkah (about 09:18 and 09:27 UTC), around May 27 and July 15
+///
+/// There are none for years out of the range of [`NaiveDate`](NaiveDate).
+/// Uses the [`Usno`](Usno) ephemeris, see [`kaaba_transits_with`](kaaba_transits_with).
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let transits = kaaba_transits(2021);
+/// assert_eq!(transits.len(), 2);
+/// assert_eq!(transits[0].date_naive(), NaiveDate::from_ymd(2021, 5, 27));
+/// assert_eq!(transits[1].date_naive(), NaiveDate::from_ymd(2021, 7, 15));
+/// ~~~~
+pub fn kaaba_transits(year: i32) -> Vec<DateTime<Utc>> {
+	kaaba_transits_with(&Usno, year)
+}
+
+/// Get the moments of a year when the sun passes over the Kaaba, with an ephemeris
+/// (see [`kaaba_transits`](kaaba_transits))
+///
+/// # Example
+/// ~~~~
+/// use prayers::*;
+///
+/// let transits = kaaba_transits_with(&Meeus::new(), 2021);
+/// assert_eq!(transits[0].date_naive(), NaiveDate::from_ymd(2021, 5, 27));
+/// ~~~~
+pub fn kaaba_transits_with<E: Ephemeris>(ephemeris: &E, year: i32) -> Vec<DateTime<Utc>> {
+	// the declination of the sun at mid-day in Makkah, relative to the latitude of the Kaaba
+	let day = |date: NaiveDate| {
+		let date = Utc.from_utc_date(&date);
+		let julian_date = julian_date(&date);
+		let noon = mid_day(ephemeris, julian_date - KAABA.1 / (15.0 * 24.0), 0.5) - KAABA.1 / 15.0;
+		let distance = ephemeris.sun_position(julian_date + noon / 24.0).0 - KAABA.0;
+		(date, noon, distance)
+	};
+
+	let mut days = (1..=366)
+		.map_while(|ordinal| NaiveDate::from_yo_opt(year, ordinal))
+		.map(day);
+	let mut transits = Vec::new();
+	let mut previous = match days.next() {
+		Some(first) => first,
+		// out of the range of dates
+		None => return transits,
+	};
+	for current in days {
+		if previous.2.signum() != current.2.signum() {
+			let (date, noon, _) = if previous.2.abs() < current.2.abs() {
+				previous
+			} else {
+				current
+			};
+			transits.push(to_date_time(date, noon));
+		}
+		previous = current;
+	}
+
+	transits
+}
+
+/// Anchor fractional hours to a UTC date, rounded to the millisecond
+fn to_date_time(date: Date<Utc>, hours: f64) -> DateTime<Utc> {
+	date.and_hms(0, 0, 0) + Duration::milliseconds((hours * 3_600_000.0).round() as i64)
+}